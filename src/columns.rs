@@ -0,0 +1,462 @@
+use std::cmp::{min, Reverse};
+use thiserror::Error;
+
+/// The columns of the pane list when they are not configured.
+pub const DEFAULT_COLUMNS: &str = "star,session,tab,id,groups,title,cmd";
+
+/// Columns that shrink keep at least this many cells, enough for a char and the ellipsis.
+const SHRINK_MIN: usize = 4;
+
+/// A column of the pane list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    /// `*` for starred panes, or the slot number for the first nine.
+    Star,
+    /// The slot number of starred panes.
+    Slot,
+    /// The session of the pane, only shown when listing sessions.
+    Session,
+    Tab,
+    Id,
+    /// `terminal` or `plugin`.
+    Kind,
+    /// The star groups of the pane, only shown when there is more than one group.
+    Groups,
+    Title,
+    /// The command of the pane, only shown when a listed pane has one.
+    Command,
+}
+
+/// How the width of a column is chosen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Width {
+    /// Always this many cells.
+    Fixed(usize),
+    /// As wide as the contents, but within min and max.
+    Range { min: usize, max: usize },
+    /// Shares the width that the other columns leave.
+    Flex,
+}
+
+/// A configured column with its width rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnSpec {
+    pub column: Column,
+    pub width: Width,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ColumnsError {
+    #[error("unknown column {0:?}")]
+    UnknownColumn(String),
+    #[error("column {0:?} is configured more than once")]
+    DuplicateColumn(String),
+    #[error("invalid width {width:?} for column {column:?}")]
+    InvalidWidth { column: String, width: String },
+    #[error("no columns are configured")]
+    Empty,
+}
+
+impl Column {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "star" => Some(Column::Star),
+            "slot" => Some(Column::Slot),
+            "session" => Some(Column::Session),
+            "tab" => Some(Column::Tab),
+            "id" => Some(Column::Id),
+            "kind" => Some(Column::Kind),
+            "groups" => Some(Column::Groups),
+            "title" => Some(Column::Title),
+            "cmd" => Some(Column::Command),
+            _ => None,
+        }
+    }
+
+    /// Return the text of the column in the table header.
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Star | Column::Slot => " ",
+            Column::Session => "Session",
+            Column::Tab => "Tab",
+            Column::Id => " ID",
+            Column::Kind => "Kind",
+            Column::Groups => "Groups",
+            Column::Title => "Pane Title",
+            Column::Command => "Command",
+        }
+    }
+
+    /// Return how important the column is, columns of lower priority are hidden
+    /// first when the pane is too narrow for all of them.
+    pub fn priority(self) -> u8 {
+        match self {
+            Column::Groups => 0,
+            Column::Kind => 1,
+            Column::Slot => 2,
+            Column::Command => 3,
+            Column::Id => 4,
+            Column::Star => 5,
+            Column::Session => 6,
+            Column::Tab => 7,
+            Column::Title => 8,
+        }
+    }
+
+    /// Return the width rule of the column when none is configured.
+    pub fn default_width(self) -> Width {
+        match self {
+            Column::Star => Width::Fixed(1),
+            Column::Slot => Width::Range { min: 1, max: 3 },
+            Column::Session => Width::Range { min: 0, max: 16 },
+            Column::Tab => Width::Range { min: 0, max: 12 },
+            Column::Id => Width::Fixed(3),
+            Column::Kind => Width::Range { min: 0, max: 8 },
+            Column::Groups => Width::Range { min: 0, max: 16 },
+            Column::Title | Column::Command => Width::Flex,
+        }
+    }
+}
+
+/// Parse a comma-separated list of columns like `star,tab:8-20,id,title,cmd:flex`.
+///
+/// A column name is optionally followed by its width rule:
+/// `N` for a fixed width, `MIN-MAX` for a width range, or `flex`.
+pub fn parse(value: &str) -> Result<Vec<ColumnSpec>, ColumnsError> {
+    let mut specs: Vec<ColumnSpec> = Vec::new();
+
+    for item in value.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+        let (name, width) = match item.split_once(':') {
+            Some((name, width)) => (name.trim(), Some(width.trim())),
+            None => (item, None),
+        };
+
+        let column =
+            Column::from_name(name).ok_or_else(|| ColumnsError::UnknownColumn(name.to_string()))?;
+        if specs.iter().any(|spec| spec.column == column) {
+            return Err(ColumnsError::DuplicateColumn(name.to_string()));
+        }
+
+        let width = match width {
+            None => column.default_width(),
+            Some(width) => parse_width(width).ok_or_else(|| ColumnsError::InvalidWidth {
+                column: name.to_string(),
+                width: width.to_string(),
+            })?,
+        };
+        specs.push(ColumnSpec { column, width });
+    }
+
+    if specs.is_empty() {
+        return Err(ColumnsError::Empty);
+    }
+    Ok(specs)
+}
+
+fn parse_width(width: &str) -> Option<Width> {
+    if width == "flex" {
+        return Some(Width::Flex);
+    }
+    if let Some((min, max)) = width.split_once('-') {
+        let (min, max) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+        return (min <= max).then_some(Width::Range { min, max });
+    }
+    width.parse().ok().map(Width::Fixed)
+}
+
+/// Return the narrowest width that a column with rule can shrink to.
+fn min_width(rule: Width) -> usize {
+    match rule {
+        Width::Fixed(n) => n,
+        Width::Range { min, max } => min.max(SHRINK_MIN.min(max)),
+        Width::Flex => SHRINK_MIN,
+    }
+}
+
+/// Return the width that a column takes without shrinking when its contents want want.
+/// Flexible columns only claim the width of their header, they get the rest anyway.
+fn natural_width(spec: &ColumnSpec, want: usize) -> usize {
+    match spec.width {
+        Width::Fixed(n) => n,
+        Width::Range { min, max } => want.clamp(min, max),
+        Width::Flex => want.min(spec.column.header().len()),
+    }
+}
+
+/// Return the columns, given with the width their contents want,
+/// that fit into width without shrinking any of them.
+/// Columns of the lowest priority are hidden first, before any column is shrunk,
+/// but the most important column is always kept.
+pub fn fit(columns: &[(ColumnSpec, usize)], width: usize) -> Vec<(ColumnSpec, usize)> {
+    let mut columns = columns.to_vec();
+    loop {
+        let needed = columns
+            .iter()
+            .map(|(spec, want)| natural_width(spec, *want))
+            .sum::<usize>()
+            + columns.len().saturating_sub(1);
+        if needed <= width || columns.len() <= 1 {
+            return columns;
+        }
+
+        let lowest = (0..columns.len())
+            .min_by_key(|&i| columns[i].0.column.priority())
+            .expect("columns is not empty");
+        columns.remove(lowest);
+    }
+}
+
+/// Distribute width between columns given as their width rule and the width
+/// their contents want, leaving one cell between neighboring columns.
+/// When the width is short, the widest range columns shrink toward their minimum
+/// first. Flexible columns share what the others leave with flex_widths.
+/// Columns that still do not fit are cut from the right so that the table never exceeds width.
+pub fn layout(columns: &[(Width, usize)], width: usize) -> Vec<usize> {
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .map(|(rule, want)| match *rule {
+            Width::Fixed(n) => n,
+            Width::Range { min, max } => (*want).clamp(min, max),
+            Width::Flex => 0,
+        })
+        .collect();
+
+    let separators = columns.len().saturating_sub(1);
+    let mut used = widths.iter().sum::<usize>() + separators;
+
+    let flex: Vec<usize> = (0..columns.len())
+        .filter(|&i| columns[i].0 == Width::Flex)
+        .collect();
+
+    let flex_min = flex.len() * SHRINK_MIN;
+    while used + flex_min > width {
+        let widest = (0..columns.len())
+            .filter(|&i| matches!(columns[i].0, Width::Range { .. }))
+            .filter(|&i| widths[i] > min_width(columns[i].0))
+            .max_by_key(|&i| widths[i]);
+        let Some(i) = widest else {
+            break;
+        };
+        widths[i] -= 1;
+        used -= 1;
+    }
+
+    let wants: Vec<usize> = flex.iter().map(|&i| columns[i].1).collect();
+    for (i, share) in flex
+        .into_iter()
+        .zip(flex_widths(width.saturating_sub(used), &wants))
+    {
+        widths[i] = share;
+    }
+
+    let mut excess = (widths.iter().sum::<usize>() + separators).saturating_sub(width);
+    for cell in widths.iter_mut().rev() {
+        let cut = min(*cell, excess);
+        *cell -= cut;
+        excess -= cut;
+    }
+    widths
+}
+
+/// Split width between columns that want the given widths.
+/// Columns that want less than an even share get what they want and the rest is
+/// shared evenly by the others. Width that is left after that goes to the first column.
+fn flex_widths(width: usize, wants: &[usize]) -> Vec<usize> {
+    let mut widths = vec![0; wants.len()];
+    let mut order: Vec<usize> = (0..wants.len()).collect();
+    order.sort_by_key(|&i| (wants[i], Reverse(i)));
+
+    let mut remaining = width;
+    for (n, &i) in order.iter().enumerate() {
+        let share = remaining / (wants.len() - n);
+        widths[i] = min(wants[i], share);
+        remaining -= widths[i];
+    }
+
+    if let Some(first) = widths.first_mut() {
+        *first += remaining;
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn spec(column: Column, width: Width) -> ColumnSpec {
+        ColumnSpec { column, width }
+    }
+
+    #[test]
+    fn parse_default() {
+        let specs = parse(DEFAULT_COLUMNS).unwrap();
+
+        let columns: Vec<Column> = specs.iter().map(|spec| spec.column).collect();
+        assert_eq!(
+            columns,
+            vec![
+                Column::Star,
+                Column::Session,
+                Column::Tab,
+                Column::Id,
+                Column::Groups,
+                Column::Title,
+                Column::Command
+            ]
+        );
+        assert!(specs
+            .iter()
+            .all(|spec| spec.width == spec.column.default_width()));
+    }
+
+    #[test]
+    fn parse_widths() {
+        assert_eq!(
+            parse(" slot:2, tab:8-20 ,kind,title:40,cmd:flex").unwrap(),
+            vec![
+                spec(Column::Slot, Width::Fixed(2)),
+                spec(Column::Tab, Width::Range { min: 8, max: 20 }),
+                spec(Column::Kind, Width::Range { min: 0, max: 8 }),
+                spec(Column::Title, Width::Fixed(40)),
+                spec(Column::Command, Width::Flex),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::unknown("star,name", ColumnsError::UnknownColumn(String::from("name")))]
+    #[case::duplicate("tab,id,tab:5", ColumnsError::DuplicateColumn(String::from("tab")))]
+    #[case::invalid_width("tab:wide", ColumnsError::InvalidWidth {
+        column: String::from("tab"),
+        width: String::from("wide"),
+    })]
+    #[case::reversed_range("tab:9-3", ColumnsError::InvalidWidth {
+        column: String::from("tab"),
+        width: String::from("9-3"),
+    })]
+    #[case::empty(" , ", ColumnsError::Empty)]
+    fn parse_invalid(#[case] value: &str, #[case] expected: ColumnsError) {
+        assert_eq!(parse(value), Err(expected));
+    }
+
+    #[rstest]
+    #[case::fixed_and_range(
+        40,
+        &[(Width::Fixed(1), 0), (Width::Range { min: 4, max: 12 }, 20), (Width::Fixed(3), 0)],
+        &[1, 12, 3]
+    )]
+    #[case::range_below_min(40, &[(Width::Range { min: 4, max: 12 }, 2)], &[4])]
+    #[case::flex_takes_the_rest(
+        30,
+        &[(Width::Fixed(1), 0), (Width::Flex, 10), (Width::Flex, 7)],
+        &[1, 20, 7]
+    )]
+    #[case::flex_shares_evenly(
+        23,
+        &[(Width::Fixed(1), 0), (Width::Flex, 30), (Width::Flex, 30)],
+        &[1, 10, 10]
+    )]
+    #[case::too_narrow(
+        3,
+        &[(Width::Fixed(3), 0), (Width::Fixed(3), 0), (Width::Flex, 10)],
+        &[1, 0, 0]
+    )]
+    #[case::fixed_cut(2, &[(Width::Fixed(3), 0)], &[2])]
+    #[case::range_shrinks(
+        20,
+        &[(Width::Range { min: 0, max: 12 }, 12), (Width::Flex, 20)],
+        &[12, 7]
+    )]
+    #[case::widest_range_shrinks_first(
+        16,
+        &[(Width::Range { min: 0, max: 12 }, 10), (Width::Range { min: 0, max: 12 }, 6), (Width::Flex, 20)],
+        &[5, 5, 4]
+    )]
+    #[case::range_stops_at_min(
+        8,
+        &[(Width::Range { min: 6, max: 12 }, 10), (Width::Flex, 20)],
+        &[6, 1]
+    )]
+    fn layout_widths(
+        #[case] width: usize,
+        #[case] columns: &[(Width, usize)],
+        #[case] expected: &[usize],
+    ) {
+        assert_eq!(layout(columns, width), expected);
+    }
+
+    #[rstest]
+    // Star 1, tab 8, id 3, groups 6, title 10, and cmd 7 with a cell between them.
+    #[case::all_fit(40, "star,tab,id,groups,title,cmd")]
+    #[case::groups_hidden_first(39, "star,tab,id,title,cmd")]
+    #[case::cmd_hidden(32, "star,tab,id,title")]
+    #[case::id_hidden(24, "star,tab,title")]
+    #[case::star_hidden(20, "tab,title")]
+    #[case::only_title(18, "title")]
+    #[case::title_is_kept(0, "title")]
+    fn fit_columns(#[case] width: usize, #[case] expected: &str) {
+        let specs = parse("star,tab,id,groups,title,cmd").unwrap();
+        let wants = [1, 8, 3, 6, 30, 20];
+        let columns: Vec<(ColumnSpec, usize)> = specs.into_iter().zip(wants).collect();
+
+        let fitted: Vec<ColumnSpec> = fit(&columns, width)
+            .into_iter()
+            .map(|(spec, _)| spec)
+            .collect();
+        assert_eq!(fitted, parse(expected).unwrap());
+    }
+
+    #[test]
+    fn fit_hides_before_shrinking() {
+        let columns = [
+            (spec(Column::Tab, Width::Range { min: 0, max: 12 }), 12),
+            (spec(Column::Id, Width::Fixed(3)), 3),
+            (spec(Column::Title, Width::Flex), 30),
+        ];
+
+        // The id is hidden instead of shrinking the tab below the 12 cells it wants.
+        let fitted = fit(&columns, 25);
+        assert_eq!(fitted, vec![columns[0], columns[2]]);
+        let wants: Vec<(Width, usize)> = fitted
+            .iter()
+            .map(|(spec, want)| (spec.width, *want))
+            .collect();
+        assert_eq!(layout(&wants, 25), vec![12, 12]);
+    }
+
+    #[test]
+    fn fit_and_layout_grid() {
+        let specs = parse(DEFAULT_COLUMNS).unwrap();
+        for width in 0..=120 {
+            for want in [0, 5, 30] {
+                let columns: Vec<(ColumnSpec, usize)> =
+                    specs.iter().map(|spec| (*spec, want)).collect();
+                let fitted = fit(&columns, width);
+                let wants: Vec<(Width, usize)> = fitted
+                    .iter()
+                    .map(|(spec, want)| (spec.width, *want))
+                    .collect();
+                let widths = layout(&wants, width);
+
+                assert!(!fitted.is_empty());
+                let total = widths.iter().sum::<usize>() + widths.len() - 1;
+                assert!(total <= width, "width {} want {}", width, want);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case::fits(30, &[10, 7], &[23, 7])]
+    #[case::even_split(20, &[30, 30], &[10, 10])]
+    #[case::short_column_first(20, &[30, 4], &[16, 4])]
+    #[case::odd_width(21, &[30, 30], &[11, 10])]
+    #[case::no_width(0, &[10, 7], &[0, 0])]
+    fn flex_widths_split(
+        #[case] width: usize,
+        #[case] wants: &[usize],
+        #[case] expected: &[usize],
+    ) {
+        assert_eq!(flex_widths(width, wants), expected);
+    }
+}
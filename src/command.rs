@@ -0,0 +1,153 @@
+use std::str::FromStr;
+use thiserror::Error;
+use zellij_tile::prelude::PaneId;
+
+pub const FOCUS: &str = "focus";
+pub const STAR: &str = "star";
+pub const UNSTAR: &str = "unstar";
+pub const TOGGLE_STAR: &str = "toggle-star";
+pub const LIST_STARS: &str = "list-stars";
+pub const LIST_PANES: &str = "list-panes";
+pub const NEXT_STAR: &str = "next-star";
+pub const PREVIOUS_STAR: &str = "previous-star";
+pub const BACK: &str = "back";
+pub const OPEN: &str = "open";
+pub const JUMP: &str = "jump";
+
+/// A command sent to the plugin with `zellij pipe --name <command> -- <argument>`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Focus the pane that matches the search query best.
+    Focus(String),
+    /// Star the pane in the active group.
+    Star(PaneId),
+    /// Unstar the pane in the active group.
+    Unstar(PaneId),
+    /// Star or unstar the pane in the active group.
+    ToggleStar(PaneId),
+    /// List the starred panes of the active group.
+    ListStars,
+    /// List all panes as JSON lines.
+    ListPanes,
+    NextStar,
+    PreviousStar,
+    /// Focus the previously focused pane.
+    Back,
+    /// Open the picker with the search query filled in.
+    Open(String),
+    /// Focus the pane that matches the search query clearly best, or open the picker.
+    Jump(String),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CommandError {
+    #[error("unknown command {0:?}")]
+    Unknown(String),
+    #[error("{0} needs an argument")]
+    MissingArgument(&'static str),
+    #[error("invalid pane ID {0:?}")]
+    InvalidPaneId(String),
+    #[error("no pane with ID {0}")]
+    NoSuchPane(String),
+    #[error("no pane matches {0:?}")]
+    NoMatch(String),
+}
+
+impl Command {
+    /// Parse the name and the payload of a pipe message.
+    pub fn parse(name: &str, payload: Option<&str>) -> Result<Self, CommandError> {
+        let argument = payload.map(str::trim).unwrap_or_default();
+        let required = |name: &'static str| {
+            if argument.is_empty() {
+                Err(CommandError::MissingArgument(name))
+            } else {
+                Ok(argument.to_string())
+            }
+        };
+
+        let pane_id = |name: &'static str| {
+            let argument = required(name)?;
+            PaneId::from_str(&argument).map_err(|_| CommandError::InvalidPaneId(argument))
+        };
+
+        match name {
+            FOCUS => required(FOCUS).map(Command::Focus),
+            STAR => pane_id(STAR).map(Command::Star),
+            UNSTAR => pane_id(UNSTAR).map(Command::Unstar),
+            TOGGLE_STAR => pane_id(TOGGLE_STAR).map(Command::ToggleStar),
+            LIST_STARS => Ok(Command::ListStars),
+            LIST_PANES => Ok(Command::ListPanes),
+            NEXT_STAR => Ok(Command::NextStar),
+            PREVIOUS_STAR => Ok(Command::PreviousStar),
+            BACK => Ok(Command::Back),
+            OPEN => Ok(Command::Open(argument.to_string())),
+            JUMP => required(JUMP).map(Command::Jump),
+            _ => Err(CommandError::Unknown(name.to_string())),
+        }
+    }
+}
+
+/// Format pane_id the way Zellij prints pane IDs, e.g., `terminal_12` or `plugin_3`.
+pub fn format_pane_id(pane_id: &PaneId) -> String {
+    match pane_id {
+        PaneId::Terminal(id) => format!("terminal_{}", id),
+        PaneId::Plugin(id) => format!("plugin_{}", id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::focus(
+        FOCUS,
+        Some(" tab:api logs\n"),
+        Ok(Command::Focus(String::from("tab:api logs")))
+    )]
+    #[case::focus_without_query(FOCUS, Some("  "), Err(CommandError::MissingArgument(FOCUS)))]
+    #[case::star_terminal(STAR, Some("12"), Ok(Command::Star(PaneId::Terminal(12))))]
+    #[case::star_plugin(STAR, Some("plugin_3"), Ok(Command::Star(PaneId::Plugin(3))))]
+    #[case::star_invalid(
+        STAR,
+        Some("vim"),
+        Err(CommandError::InvalidPaneId(String::from("vim")))
+    )]
+    #[case::star_without_id(STAR, None, Err(CommandError::MissingArgument(STAR)))]
+    #[case::unstar(UNSTAR, Some("terminal_12"), Ok(Command::Unstar(PaneId::Terminal(12))))]
+    #[case::unstar_without_id(UNSTAR, None, Err(CommandError::MissingArgument(UNSTAR)))]
+    #[case::toggle_star(TOGGLE_STAR, Some("4"), Ok(Command::ToggleStar(PaneId::Terminal(4))))]
+    #[case::toggle_star_invalid(
+        TOGGLE_STAR,
+        Some("x"),
+        Err(CommandError::InvalidPaneId(String::from("x")))
+    )]
+    #[case::list_stars(LIST_STARS, None, Ok(Command::ListStars))]
+    #[case::list_panes(LIST_PANES, None, Ok(Command::ListPanes))]
+    #[case::next_star(NEXT_STAR, None, Ok(Command::NextStar))]
+    #[case::previous_star(PREVIOUS_STAR, None, Ok(Command::PreviousStar))]
+    #[case::back(BACK, Some("ignored"), Ok(Command::Back))]
+    #[case::open(
+        OPEN,
+        Some("is:starred"),
+        Ok(Command::Open(String::from("is:starred")))
+    )]
+    #[case::open_empty(OPEN, None, Ok(Command::Open(String::new())))]
+    #[case::jump(JUMP, Some("psql"), Ok(Command::Jump(String::from("psql"))))]
+    #[case::jump_without_query(JUMP, None, Err(CommandError::MissingArgument(JUMP)))]
+    #[case::unknown("5f1c2d", None, Err(CommandError::Unknown(String::from("5f1c2d"))))]
+    fn parse(
+        #[case] name: &str,
+        #[case] payload: Option<&str>,
+        #[case] expected: Result<Command, CommandError>,
+    ) {
+        assert_eq!(Command::parse(name, payload), expected);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_pane_id(&PaneId::Terminal(12)), "terminal_12");
+        assert_eq!(format_pane_id(&PaneId::Plugin(3)), "plugin_3");
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+use crate::columns;
+use crate::focus;
+use crate::history;
+use crate::text;
+use crate::theme;
+
+const STAR_GROUPS: &str = "star_groups";
+const FOCUS_HISTORY_SIZE: &str = "focus_history_size";
+const SORT_BY: &str = "sort_by";
+const SEARCH_HISTORY_SIZE: &str = "search_history_size";
+const PREFILL_LAST_QUERY: &str = "prefill_last_query";
+const GLOBAL_STARS: &str = "global_stars";
+const JUMP_SCORE_GAP: &str = "jump_score_gap";
+const LAYOUT: &str = "layout";
+const COLUMNS: &str = "columns";
+const ELLIPSIS: &str = "ellipsis";
+const THEME_PROMPT: &str = "theme_prompt";
+const THEME_QUERY: &str = "theme_query";
+const THEME_STAR: &str = "theme_star";
+const THEME_CURRENT_PANE: &str = "theme_current_pane";
+const THEME_PREVIOUS_PANE: &str = "theme_previous_pane";
+const THEME_MATCH: &str = "theme_match";
+const THEME_HEADER: &str = "theme_header";
+const THEME_DIMMED: &str = "theme_dimmed";
+const THEME_MARK: &str = "theme_mark";
+const THEME_SESSION: &str = "theme_session";
+const THEME_ERROR: &str = "theme_error";
+
+/// The score gap that makes the best match clear enough to jump to by default.
+pub const DEFAULT_JUMP_SCORE_GAP: u32 = 20;
+
+/// The order of the panes in the picker when there is no search query,
+/// and the tiebreaker between panes with the same match score otherwise.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    /// The order of the tabs and the panes in them.
+    #[default]
+    Tab,
+    /// The most recently focused pane first.
+    Mru,
+    /// The most frequently and recently focused pane first.
+    Frecency,
+}
+
+/// How the picker lists the panes.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// A table with the tab name on every row.
+    #[default]
+    Flat,
+    /// A header row per tab with its panes below, tabs can be collapsed.
+    Tree,
+}
+
+/// Plugin settings other than the key bindings.
+pub struct Config {
+    pub star_groups: Vec<String>,
+    pub focus_history_size: usize,
+    pub sort_by: SortBy,
+    pub search_history_size: usize,
+    pub prefill_last_query: bool,
+    pub global_stars: bool,
+    pub jump_score_gap: u32,
+    pub layout: Layout,
+    pub columns: Vec<columns::ColumnSpec>,
+    pub ellipsis: text::Ellipsis, // Where titles and commands that do not fit are clipped.
+    pub theme: theme::Theme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            star_groups: Vec::new(),
+            focus_history_size: focus::DEFAULT_CAPACITY,
+            sort_by: SortBy::default(),
+            search_history_size: history::DEFAULT_CAPACITY,
+            prefill_last_query: false,
+            global_stars: false,
+            jump_score_gap: DEFAULT_JUMP_SCORE_GAP,
+            layout: Layout::default(),
+            columns: columns::parse(columns::DEFAULT_COLUMNS).expect("default columns are valid"),
+            ellipsis: text::Ellipsis::default(),
+            theme: theme::Theme::default(),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ConfigError {
+    #[error("star group {0:?} is configured more than once")]
+    DuplicateStarGroup(String),
+    #[error("invalid value {value:?} for {key}")]
+    InvalidValue { key: &'static str, value: String },
+    #[error("invalid columns: {0}")]
+    Columns(#[from] columns::ColumnsError),
+}
+
+impl Config {
+    /// Parse the plugin configuration option by option.
+    /// An invalid option keeps its default and yields an error, while the other options still apply.
+    pub fn parse(map: &BTreeMap<String, String>) -> (Self, Vec<ConfigError>) {
+        let mut config = Config::default();
+        let mut errors = Vec::new();
+
+        if let Some(value) = map.get(STAR_GROUPS) {
+            for name in split_list(value) {
+                if config.star_groups.contains(&name) {
+                    errors.push(ConfigError::DuplicateStarGroup(name));
+                } else {
+                    config.star_groups.push(name);
+                }
+            }
+        }
+
+        if let Some(value) = map.get(FOCUS_HISTORY_SIZE) {
+            let size = value
+                .parse()
+                .map_err(|_| invalid(FOCUS_HISTORY_SIZE, value));
+            set(&mut config.focus_history_size, size, &mut errors);
+        }
+
+        if let Some(value) = map.get(SORT_BY) {
+            let sort_by = match value.as_str() {
+                "tab" => Ok(SortBy::Tab),
+                "mru" => Ok(SortBy::Mru),
+                "frecency" => Ok(SortBy::Frecency),
+                _ => Err(invalid(SORT_BY, value)),
+            };
+            set(&mut config.sort_by, sort_by, &mut errors);
+        }
+
+        if let Some(value) = map.get(SEARCH_HISTORY_SIZE) {
+            let size = value
+                .parse()
+                .map_err(|_| invalid(SEARCH_HISTORY_SIZE, value));
+            set(&mut config.search_history_size, size, &mut errors);
+        }
+
+        if let Some(value) = map.get(PREFILL_LAST_QUERY) {
+            let prefill = value
+                .parse()
+                .map_err(|_| invalid(PREFILL_LAST_QUERY, value));
+            set(&mut config.prefill_last_query, prefill, &mut errors);
+        }
+
+        if let Some(value) = map.get(GLOBAL_STARS) {
+            let global_stars = value.parse().map_err(|_| invalid(GLOBAL_STARS, value));
+            set(&mut config.global_stars, global_stars, &mut errors);
+        }
+
+        if let Some(value) = map.get(JUMP_SCORE_GAP) {
+            let gap = value.parse().map_err(|_| invalid(JUMP_SCORE_GAP, value));
+            set(&mut config.jump_score_gap, gap, &mut errors);
+        }
+
+        if let Some(value) = map.get(LAYOUT) {
+            let layout = match value.as_str() {
+                "flat" => Ok(Layout::Flat),
+                "tree" => Ok(Layout::Tree),
+                _ => Err(invalid(LAYOUT, value)),
+            };
+            set(&mut config.layout, layout, &mut errors);
+        }
+
+        if let Some(value) = map.get(COLUMNS) {
+            let columns = columns::parse(value).map_err(ConfigError::from);
+            set(&mut config.columns, columns, &mut errors);
+        }
+
+        if let Some(value) = map.get(ELLIPSIS) {
+            let ellipsis = match value.as_str() {
+                "end" => Ok(text::Ellipsis::End),
+                "middle" => Ok(text::Ellipsis::Middle),
+                _ => Err(invalid(ELLIPSIS, value)),
+            };
+            set(&mut config.ellipsis, ellipsis, &mut errors);
+        }
+
+        let theme = &mut config.theme;
+        for (key, color) in [
+            (THEME_PROMPT, &mut theme.prompt),
+            (THEME_QUERY, &mut theme.query),
+            (THEME_STAR, &mut theme.star),
+            (THEME_CURRENT_PANE, &mut theme.current_pane),
+            (THEME_PREVIOUS_PANE, &mut theme.previous_pane),
+            (THEME_MATCH, &mut theme.matched),
+            (THEME_HEADER, &mut theme.header),
+            (THEME_DIMMED, &mut theme.dimmed),
+            (THEME_MARK, &mut theme.mark),
+            (THEME_SESSION, &mut theme.session),
+            (THEME_ERROR, &mut theme.error),
+        ] {
+            if let Some(value) = map.get(key) {
+                let parsed = theme::parse_color(value).ok_or_else(|| invalid(key, value));
+                set(color, parsed, &mut errors);
+            }
+        }
+
+        (config, errors)
+    }
+}
+
+/// Store a parsed value into field, or keep the field and collect the error.
+fn set<T>(field: &mut T, parsed: Result<T, ConfigError>, errors: &mut Vec<ConfigError>) {
+    match parsed {
+        Ok(value) => *field = value,
+        Err(err) => errors.push(err),
+    }
+}
+
+fn invalid(key: &'static str, value: &str) -> ConfigError {
+    ConfigError::InvalidValue {
+        key,
+        value: value.to_string(),
+    }
+}
+
+/// Split a comma-separated value into its trimmed, non-empty items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn test_parse() {
+        let map = BTreeMap::from([
+            (
+                STAR_GROUPS.to_string(),
+                String::from(" frontend,backend , ,ops"),
+            ),
+            (FOCUS_HISTORY_SIZE.to_string(), String::from("10")),
+            (SORT_BY.to_string(), String::from("mru")),
+            (SEARCH_HISTORY_SIZE.to_string(), String::from("20")),
+            (PREFILL_LAST_QUERY.to_string(), String::from("true")),
+            (GLOBAL_STARS.to_string(), String::from("true")),
+            (JUMP_SCORE_GAP.to_string(), String::from("0")),
+            (LAYOUT.to_string(), String::from("tree")),
+            (COLUMNS.to_string(), String::from("slot,tab:8-20,title")),
+            (THEME_CURRENT_PANE.to_string(), String::from("2")),
+            (THEME_MATCH.to_string(), String::from("none")),
+            (THEME_MARK.to_string(), String::from("3")),
+            (ELLIPSIS.to_string(), String::from("middle")),
+        ]);
+
+        let (config, errors) = Config::parse(&map);
+
+        assert_eq!(errors, vec![]);
+        assert_eq!(config.star_groups, vec!["frontend", "backend", "ops"]);
+        assert_eq!(config.focus_history_size, 10);
+        assert_eq!(config.sort_by, SortBy::Mru);
+        assert_eq!(config.search_history_size, 20);
+        assert!(config.prefill_last_query);
+        assert!(config.global_stars);
+        assert_eq!(config.jump_score_gap, 0);
+        assert_eq!(config.layout, Layout::Tree);
+        assert_eq!(
+            config.columns,
+            columns::parse("slot,tab:8-20,title").unwrap()
+        );
+        assert_eq!(config.ellipsis, text::Ellipsis::Middle);
+        assert_eq!(config.theme.current_pane, Some(2));
+        assert_eq!(config.theme.matched, None);
+        assert_eq!(config.theme.mark, Some(3));
+        assert_eq!(config.theme.prompt, theme::Theme::default().prompt);
+    }
+
+    #[test]
+    fn test_parse_default() {
+        let (config, errors) = Config::parse(&BTreeMap::new());
+
+        assert_eq!(errors, vec![]);
+        assert!(config.star_groups.is_empty());
+        assert_eq!(config.focus_history_size, focus::DEFAULT_CAPACITY);
+        assert_eq!(config.sort_by, SortBy::Tab);
+        assert_eq!(config.search_history_size, history::DEFAULT_CAPACITY);
+        assert!(!config.prefill_last_query);
+        assert!(!config.global_stars);
+        assert_eq!(config.jump_score_gap, DEFAULT_JUMP_SCORE_GAP);
+        assert_eq!(config.layout, Layout::Flat);
+        assert_eq!(
+            config.columns,
+            columns::parse(columns::DEFAULT_COLUMNS).unwrap()
+        );
+        assert_eq!(config.ellipsis, text::Ellipsis::End);
+        assert_eq!(config.theme, theme::Theme::default());
+    }
+
+    #[rstest]
+    #[case(FOCUS_HISTORY_SIZE, "-1")]
+    #[case(FOCUS_HISTORY_SIZE, "many")]
+    #[case(SORT_BY, "title")]
+    #[case(SEARCH_HISTORY_SIZE, "lots")]
+    #[case(PREFILL_LAST_QUERY, "yes")]
+    #[case(GLOBAL_STARS, "on")]
+    #[case(JUMP_SCORE_GAP, "-5")]
+    #[case(LAYOUT, "grid")]
+    #[case(ELLIPSIS, "start")]
+    #[case(THEME_PROMPT, "4")]
+    #[case(THEME_DIMMED, "grey")]
+    fn test_parse_invalid_value(#[case] key: &'static str, #[case] value: &str) {
+        let map = BTreeMap::from([(key.to_string(), value.to_string())]);
+
+        let (_, errors) = Config::parse(&map);
+
+        assert_eq!(
+            errors,
+            vec![ConfigError::InvalidValue {
+                key,
+                value: value.to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_keeps_valid_values() {
+        let map = BTreeMap::from([
+            (SORT_BY.to_string(), String::from("title")),
+            (LAYOUT.to_string(), String::from("tree")),
+            (COLUMNS.to_string(), String::from("star,name")),
+            (THEME_STAR.to_string(), String::from("2")),
+            (THEME_MATCH.to_string(), String::from("red")),
+        ]);
+
+        let (config, errors) = Config::parse(&map);
+
+        // Every invalid option is reported and keeps its default.
+        assert_eq!(
+            errors,
+            vec![
+                invalid(SORT_BY, "title"),
+                ConfigError::Columns(columns::ColumnsError::UnknownColumn(String::from("name"))),
+                invalid(THEME_MATCH, "red"),
+            ]
+        );
+        assert_eq!(config.sort_by, SortBy::Tab);
+        assert_eq!(
+            config.columns,
+            columns::parse(columns::DEFAULT_COLUMNS).unwrap()
+        );
+        assert_eq!(config.theme.matched, theme::Theme::default().matched);
+        assert_eq!(config.layout, Layout::Tree);
+        assert_eq!(config.theme.star, Some(2));
+    }
+
+    #[test]
+    fn test_parse_unknown_column() {
+        let map = BTreeMap::from([(COLUMNS.to_string(), String::from("star,name"))]);
+
+        let (_, errors) = Config::parse(&map);
+
+        assert_eq!(
+            errors,
+            vec![ConfigError::Columns(columns::ColumnsError::UnknownColumn(
+                String::from("name")
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_parse_duplicate_star_group() {
+        let map = BTreeMap::from([(STAR_GROUPS.to_string(), String::from("ops,dev,ops"))]);
+
+        let (config, errors) = Config::parse(&map);
+
+        assert_eq!(
+            errors,
+            vec![ConfigError::DuplicateStarGroup(String::from("ops"))]
+        );
+        assert_eq!(config.star_groups, vec!["ops", "dev"]);
+    }
+}
@@ -0,0 +1,159 @@
+/// A single line of text with a cursor, used for the search prompt.
+///
+/// The cursor is a char index into the text, so it always sits
+/// on a char boundary.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl From<&str> for LineEditor {
+    /// Create an editor with the cursor at the end of text.
+    fn from(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+        }
+    }
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Return the char index of the cursor.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Insert c before the cursor.
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Insert s before the cursor, replacing line breaks with spaces.
+    pub fn insert_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.insert(if c == '\n' || c == '\r' { ' ' } else { c });
+        }
+    }
+
+    /// Delete the char before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.delete();
+        }
+    }
+
+    /// Delete the char under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            let at = self.byte_index(self.cursor);
+            self.text.remove(at);
+        }
+    }
+
+    /// Delete the word before the cursor and the whitespace after it.
+    pub fn delete_word(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        let range = self.byte_index(start)..self.byte_index(self.cursor);
+        self.text.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Return the byte index of the char at char_index.
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(char_index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    /// Create an editor from text where `|` marks the cursor.
+    fn editor(marked: &str) -> LineEditor {
+        let cursor = marked.chars().position(|c| c == '|').unwrap();
+        LineEditor {
+            text: marked.replacen('|', "", 1),
+            cursor,
+        }
+    }
+
+    #[rstest]
+    #[case::insert_at_end("ab|", |e: &mut LineEditor| e.insert('c'), "abc|")]
+    #[case::insert_in_middle("a|c", |e: &mut LineEditor| e.insert('b'), "ab|c")]
+    #[case::insert_multibyte("ä|ö", |e: &mut LineEditor| e.insert('ü'), "äü|ö")]
+    #[case::paste("a|d", |e: &mut LineEditor| e.insert_str("b\nc"), "ab c|d")]
+    #[case::backspace("ab|c", |e: &mut LineEditor| e.backspace(), "a|c")]
+    #[case::backspace_at_start("|abc", |e: &mut LineEditor| e.backspace(), "|abc")]
+    #[case::delete("a|bc", |e: &mut LineEditor| e.delete(), "a|c")]
+    #[case::delete_at_end("abc|", |e: &mut LineEditor| e.delete(), "abc|")]
+    #[case::delete_word("tab:api  logs|", |e: &mut LineEditor| e.delete_word(), "tab:api  |")]
+    #[case::delete_word_and_space("tab:api  |logs", |e: &mut LineEditor| e.delete_word(), "|logs")]
+    #[case::delete_word_at_start("|logs", |e: &mut LineEditor| e.delete_word(), "|logs")]
+    #[case::clear("ab|c", |e: &mut LineEditor| e.clear(), "|")]
+    #[case::left("ab|c", |e: &mut LineEditor| e.move_left(), "a|bc")]
+    #[case::left_at_start("|abc", |e: &mut LineEditor| e.move_left(), "|abc")]
+    #[case::right("ab|c", |e: &mut LineEditor| e.move_right(), "abc|")]
+    #[case::right_at_end("abc|", |e: &mut LineEditor| e.move_right(), "abc|")]
+    #[case::home("ab|c", |e: &mut LineEditor| e.move_home(), "|abc")]
+    #[case::end("a|bc", |e: &mut LineEditor| e.move_end(), "abc|")]
+    fn edit(#[case] before: &str, #[case] action: fn(&mut LineEditor), #[case] after: &str) {
+        let mut editor = editor(before);
+
+        action(&mut editor);
+
+        assert_eq!(editor, self::editor(after));
+    }
+
+    #[test]
+    fn from_puts_cursor_at_end() {
+        let editor = LineEditor::from("äbc");
+
+        assert_eq!(editor.text(), "äbc");
+        assert_eq!(editor.cursor(), 3);
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use zellij_tile::prelude::PaneId;
+
+/// The number of panes remembered by default.
+pub const DEFAULT_CAPACITY: usize = 50;
+
+/// A bounded most-recently-used history of focused panes.
+///
+/// Walking the history with back and forward works like in a browser:
+/// the order of the history is kept while walking, and the pane that the walk
+/// ended on is moved to the front once another pane gets focus.
+pub struct FocusHistory {
+    // The most recently focused pane is at the front.
+    entries: VecDeque<PaneId>,
+    capacity: usize,
+
+    // Index of the currently focused pane in entries, non-zero while walking.
+    cursor: usize,
+}
+
+impl Default for FocusHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl FocusHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(2),
+            cursor: 0,
+        }
+    }
+
+    /// Return the currently focused pane.
+    pub fn current(&self) -> Option<PaneId> {
+        self.entries.get(self.cursor).copied()
+    }
+
+    /// Return the pane that was focused before the current one.
+    pub fn previous(&self) -> Option<PaneId> {
+        let index = if self.cursor == 0 { 1 } else { 0 };
+        self.entries.get(index).copied()
+    }
+
+    /// Record that pane_id got focus.
+    ///
+    /// Panes that are not in pane_ids anymore are dropped from the history,
+    /// so the previous pane is always one that still exists.
+    pub fn visit(&mut self, pane_id: PaneId, pane_ids: &HashSet<PaneId>) {
+        if self.current() == Some(pane_id) {
+            return;
+        }
+
+        // End the walk by moving the pane where it stopped to the front.
+        if self.cursor > 0 {
+            if let Some(walked) = self.entries.remove(self.cursor) {
+                self.entries.push_front(walked);
+            }
+            self.cursor = 0;
+        }
+
+        self.entries
+            .retain(|id| pane_ids.contains(id) && *id != pane_id);
+        self.entries.push_front(pane_id);
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Move one step back in the history, skipping panes that are not in pane_ids,
+    /// and return the pane to focus.
+    pub fn back(&mut self, pane_ids: &HashSet<PaneId>) -> Option<PaneId> {
+        let index = (self.cursor + 1..self.entries.len())
+            .find(|&index| pane_ids.contains(&self.entries[index]))?;
+        self.cursor = index;
+        self.current()
+    }
+
+    /// Move one step forward in the history, skipping panes that are not in pane_ids,
+    /// and return the pane to focus.
+    pub fn forward(&mut self, pane_ids: &HashSet<PaneId>) -> Option<PaneId> {
+        let index = (0..self.cursor)
+            .rev()
+            .find(|&index| pane_ids.contains(&self.entries[index]))?;
+        self.cursor = index;
+        self.current()
+    }
+
+    /// Return the position of pane_id in most-recently-used order,
+    /// where the current pane is at position 0.
+    pub fn rank(&self, pane_id: &PaneId) -> Option<usize> {
+        let index = self.entries.iter().position(|id| id == pane_id)?;
+
+        Some(if index == self.cursor {
+            0
+        } else if index < self.cursor {
+            index + 1
+        } else {
+            index
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn ids(ids: &[u32]) -> HashSet<PaneId> {
+        ids.iter().map(|id| PaneId::Terminal(*id)).collect()
+    }
+
+    fn history(ids: &[u32]) -> FocusHistory {
+        FocusHistory {
+            entries: ids.iter().map(|id| PaneId::Terminal(*id)).collect(),
+            capacity: DEFAULT_CAPACITY,
+            cursor: 0,
+        }
+    }
+
+    fn entries(history: &FocusHistory) -> Vec<u32> {
+        history
+            .entries
+            .iter()
+            .map(|id| match id {
+                PaneId::Terminal(id) | PaneId::Plugin(id) => *id,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty() {
+        let mut history = FocusHistory::default();
+
+        assert_eq!(history.current(), None);
+        assert_eq!(history.previous(), None);
+        assert_eq!(history.back(&ids(&[1])), None);
+        assert_eq!(history.forward(&ids(&[1])), None);
+    }
+
+    #[rstest]
+    #[case::new_pane(&[1, 2, 3], 4, &[1, 2, 3, 4], &[4, 1, 2, 3])]
+    #[case::known_pane(&[1, 2, 3], 3, &[1, 2, 3], &[3, 1, 2])]
+    #[case::current_pane(&[1, 2, 3], 1, &[1, 2, 3], &[1, 2, 3])]
+    #[case::prune_closed_panes(&[1, 2, 3], 4, &[3, 4], &[4, 3])]
+    fn visit(
+        #[case] initial: &[u32],
+        #[case] focus: u32,
+        #[case] existing: &[u32],
+        #[case] expected: &[u32],
+    ) {
+        let mut history = history(initial);
+
+        history.visit(PaneId::Terminal(focus), &ids(existing));
+
+        assert_eq!(entries(&history), expected);
+        assert_eq!(history.current(), Some(PaneId::Terminal(focus)));
+    }
+
+    #[test]
+    fn visit_is_bounded() {
+        let mut history = FocusHistory::new(3);
+        let existing = ids(&[1, 2, 3, 4]);
+
+        for id in 1..=4 {
+            history.visit(PaneId::Terminal(id), &existing);
+        }
+
+        assert_eq!(entries(&history), [4, 3, 2]);
+    }
+
+    #[test]
+    fn back_and_forward() {
+        let mut history = history(&[1, 2, 3, 4]);
+        let existing = ids(&[1, 2, 4]);
+
+        assert_eq!(history.back(&existing), Some(PaneId::Terminal(2)));
+        // Pane 3 is closed and skipped.
+        assert_eq!(history.back(&existing), Some(PaneId::Terminal(4)));
+        assert_eq!(history.back(&existing), None);
+        assert_eq!(history.current(), Some(PaneId::Terminal(4)));
+        assert_eq!(history.previous(), Some(PaneId::Terminal(1)));
+
+        assert_eq!(history.forward(&existing), Some(PaneId::Terminal(2)));
+        assert_eq!(history.forward(&existing), Some(PaneId::Terminal(1)));
+        assert_eq!(history.forward(&existing), None);
+
+        // Walking does not change the order.
+        assert_eq!(entries(&history), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn visit_after_back() {
+        let mut history = history(&[1, 2, 3, 4]);
+        let existing = ids(&[1, 2, 3, 4, 5]);
+
+        history.back(&existing);
+        history.back(&existing);
+        assert_eq!(history.current(), Some(PaneId::Terminal(3)));
+
+        // Focus arriving at the pane we walked to is not a new visit.
+        history.visit(PaneId::Terminal(3), &existing);
+        assert_eq!(entries(&history), [1, 2, 3, 4]);
+        assert_eq!(history.rank(&PaneId::Terminal(3)), Some(0));
+        assert_eq!(history.rank(&PaneId::Terminal(1)), Some(1));
+        assert_eq!(history.rank(&PaneId::Terminal(2)), Some(2));
+        assert_eq!(history.rank(&PaneId::Terminal(4)), Some(3));
+        assert_eq!(history.rank(&PaneId::Terminal(5)), None);
+
+        history.visit(PaneId::Terminal(5), &existing);
+        assert_eq!(entries(&history), [5, 3, 1, 2, 4]);
+        assert_eq!(history.previous(), Some(PaneId::Terminal(3)));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::preview::{Layout, Rect};
+
+/// The plugin pane needs this many columns to show the table.
+const TABLE_MIN_COLS: usize = 24;
+/// The plugin pane needs this many rows to show the prompt, the table header, and two rows.
+const TABLE_MIN_ROWS: usize = 6;
+/// The plugin pane needs this many columns to show the compact list.
+const COMPACT_MIN_COLS: usize = 10;
+/// The plugin pane needs this many rows to show the prompt and one line of the compact list.
+const COMPACT_MIN_ROWS: usize = 2;
+
+/// How the picker is drawn into the plugin pane.
+///
+/// Smaller panes get less: the table, then a compact list without a header,
+/// and finally only a message that the pane is too small.
+#[derive(Debug, PartialEq)]
+pub enum Frame {
+    /// The prompt above the table, with the preview next to or below it when it fits.
+    Table {
+        prompt: Rect,
+        layout: Layout,
+    },
+    /// The prompt on the first row and one line per item below it.
+    Compact {
+        prompt: Rect,
+        list: Rect,
+    },
+    TooSmall,
+}
+
+impl Frame {
+    pub fn new(rows: usize, cols: usize, show_preview: bool) -> Self {
+        if rows >= TABLE_MIN_ROWS && cols >= TABLE_MIN_COLS {
+            let area = Rect {
+                x: 1,
+                y: 3,
+                cols: cols - 1,
+                rows: rows - 3,
+            };
+            Frame::Table {
+                prompt: Rect {
+                    x: 1,
+                    y: 1,
+                    cols: cols - 1,
+                    rows: 1,
+                },
+                layout: Layout::new(area, show_preview),
+            }
+        } else if rows >= COMPACT_MIN_ROWS && cols >= COMPACT_MIN_COLS {
+            Frame::Compact {
+                prompt: Rect {
+                    x: 0,
+                    y: 0,
+                    cols,
+                    rows: 1,
+                },
+                list: Rect {
+                    x: 0,
+                    y: 1,
+                    cols,
+                    rows: rows - 1,
+                },
+            }
+        } else {
+            Frame::TooSmall
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn inside(rect: &Rect, rows: usize, cols: usize) -> bool {
+        rect.x + rect.cols <= cols && rect.y + rect.rows <= rows
+    }
+
+    #[rstest]
+    #[case::table(40, 120, "table")]
+    #[case::smallest_table(6, 24, "table")]
+    #[case::too_short_for_table(5, 80, "compact")]
+    #[case::too_narrow_for_table(40, 23, "compact")]
+    #[case::smallest_compact(2, 10, "compact")]
+    #[case::one_row(1, 80, "too small")]
+    #[case::too_narrow(40, 9, "too small")]
+    #[case::empty(0, 0, "too small")]
+    fn choose_frame(#[case] rows: usize, #[case] cols: usize, #[case] expected: &str) {
+        let got = match Frame::new(rows, cols, true) {
+            Frame::Table { .. } => "table",
+            Frame::Compact { .. } => "compact",
+            Frame::TooSmall => "too small",
+        };
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn frames_stay_inside_the_pane() {
+        for rows in 0..=45 {
+            for cols in 0..=130 {
+                for show_preview in [false, true] {
+                    match Frame::new(rows, cols, show_preview) {
+                        Frame::Table { prompt, layout } => {
+                            assert!(inside(&prompt, rows, cols));
+                            assert!(inside(&layout.list, rows, cols));
+                            assert!(layout.list.rows >= TABLE_MIN_ROWS - 3);
+                            if let Some(preview) = layout.preview {
+                                assert!(inside(&preview, rows, cols), "{}x{}", rows, cols);
+                            }
+                        }
+                        Frame::Compact { prompt, list } => {
+                            assert!(inside(&prompt, rows, cols));
+                            assert!(inside(&list, rows, cols));
+                            assert!(list.rows >= 1);
+                        }
+                        Frame::TooSmall => {
+                            assert!(rows < COMPACT_MIN_ROWS || cols < COMPACT_MIN_COLS);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use zellij_tile::prelude::PaneId;
+
+/// How often and how recently a pane was focused.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Visits {
+    count: u32,
+    last: u64,
+}
+
+/// Scores panes by how often and how recently they were focused.
+///
+/// Time is counted in focus changes rather than wall-clock time,
+/// so the scores do not decay while the session is idle.
+#[derive(Default)]
+pub struct Frecency {
+    visits: HashMap<PaneId, Visits>,
+    clock: u64,
+    current: Option<PaneId>,
+}
+
+impl Frecency {
+    /// Record that pane_id got focus.
+    /// Repeated calls for the pane that already has focus are ignored.
+    pub fn visit(&mut self, pane_id: PaneId) {
+        if self.current == Some(pane_id) {
+            return;
+        }
+
+        self.clock += 1;
+        self.current = Some(pane_id);
+
+        let visits = self.visits.entry(pane_id).or_insert(Visits {
+            count: 0,
+            last: self.clock,
+        });
+        visits.count = visits.count.saturating_add(1);
+        visits.last = self.clock;
+    }
+
+    /// Return the frecency score of pane_id, higher is more relevant.
+    /// Panes that were never focused score 0.
+    pub fn score(&self, pane_id: &PaneId) -> u32 {
+        self.visits.get(pane_id).map_or(0, |visits| {
+            visits
+                .count
+                .saturating_mul(recency_weight(self.clock - visits.last))
+        })
+    }
+
+    /// Forget the panes that are not in pane_ids.
+    pub fn sync(&mut self, pane_ids: &HashSet<PaneId>) {
+        self.visits.retain(|id, _| pane_ids.contains(id));
+    }
+}
+
+/// Weight a visit by the number of focus changes since it happened.
+fn recency_weight(age: u64) -> u32 {
+    match age {
+        0..=3 => 100,
+        4..=15 => 70,
+        16..=63 => 50,
+        64..=255 => 30,
+        _ => 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn score() {
+        let mut frecency = Frecency::default();
+
+        frecency.visit(PaneId::Terminal(1));
+        frecency.visit(PaneId::Terminal(1)); // Still focused, not another visit.
+        frecency.visit(PaneId::Terminal(2));
+        frecency.visit(PaneId::Terminal(1));
+
+        assert_eq!(frecency.score(&PaneId::Terminal(1)), 200);
+        assert_eq!(frecency.score(&PaneId::Terminal(2)), 100);
+        assert_eq!(frecency.score(&PaneId::Terminal(3)), 0);
+    }
+
+    #[test]
+    fn frequent_beats_recent() {
+        let mut frecency = Frecency::default();
+
+        for _ in 0..5 {
+            frecency.visit(PaneId::Terminal(1));
+            frecency.visit(PaneId::Terminal(2));
+        }
+        for id in 3..=6 {
+            frecency.visit(PaneId::Terminal(id));
+        }
+
+        assert_eq!(frecency.score(&PaneId::Terminal(1)), 350);
+        assert_eq!(frecency.score(&PaneId::Terminal(6)), 100);
+    }
+
+    #[test]
+    fn sync() {
+        let mut frecency = Frecency::default();
+        frecency.visit(PaneId::Terminal(1));
+        frecency.visit(PaneId::Terminal(2));
+
+        frecency.sync(&HashSet::from([PaneId::Terminal(2)]));
+
+        assert_eq!(frecency.score(&PaneId::Terminal(1)), 0);
+        assert_eq!(frecency.score(&PaneId::Terminal(2)), 100);
+    }
+
+    #[rstest]
+    #[case(0, 100)]
+    #[case(3, 100)]
+    #[case(4, 70)]
+    #[case(63, 50)]
+    #[case(64, 30)]
+    #[case(256, 10)]
+    fn weight(#[case] age: u64, #[case] expected: u32) {
+        assert_eq!(recency_weight(age), expected);
+    }
+}
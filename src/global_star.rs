@@ -0,0 +1,481 @@
+use crate::star::{self, PaneKey};
+
+/// Header of the on-disk format written by [GlobalStars::encode].
+const FORMAT_HEADER: &str = "# zellij-pane-picker global stars v1";
+
+const STATE_STARRED: &str = "+";
+const STATE_UNSTARRED: &str = "-";
+
+/// Unstarred records are kept this long so that the removal reaches the other
+/// sessions before the record is dropped from the file.
+pub const TOMBSTONE_TTL_MS: u64 = 7 * 24 * 60 * 60 * 1000;
+
+/// A pane starred in the global list, shared between sessions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalStar {
+    pub session: String,
+    pub key: PaneKey,
+}
+
+/// The last change to a pane in the global list.
+#[derive(Debug, Clone, PartialEq)]
+struct Record {
+    star: GlobalStar,
+    starred: bool,
+    updated: u64, // Milliseconds since the Unix epoch.
+}
+
+/// Starred panes of all sessions, kept in a file in the plugin data directory.
+///
+/// Every session writes the whole list, so each session merges the file into its
+/// own list before writing it back. The merge keeps the latest change of each pane,
+/// and unstarred panes are kept as tombstones for a while so that an unstar is not
+/// undone by a session that still has the star.
+///
+/// The file is not locked, so a session that writes between the read and the write
+/// of another session still loses its change. As each session only changes the
+/// records of its own panes and keeps them in memory, its next write brings them back.
+#[derive(Debug, Default, PartialEq)]
+pub struct GlobalStars {
+    records: Vec<Record>,
+}
+
+impl GlobalStars {
+    /// Star or unstar a pane at the time now.
+    pub fn set(&mut self, star: GlobalStar, starred: bool, now: u64) {
+        match self.records.iter_mut().find(|record| record.star == star) {
+            Some(record) => {
+                record.starred = starred;
+                record.updated = now;
+            }
+            None => self.records.push(Record {
+                star,
+                starred,
+                updated: now,
+            }),
+        }
+    }
+
+    /// Make the records of session follow starred, the panes of session that are starred.
+    /// Starred records of the other panes of session, e.g., of closed or renamed panes,
+    /// are unstarred unless keep returns true for them. Return whether the list changed.
+    pub fn sync_session<F>(&mut self, session: &str, starred: &[PaneKey], keep: F, now: u64) -> bool
+    where
+        F: Fn(&PaneKey) -> bool,
+    {
+        let mut changed = false;
+        for record in &mut self.records {
+            if record.starred
+                && record.star.session == session
+                && !starred.contains(&record.star.key)
+                && !keep(&record.star.key)
+            {
+                record.starred = false;
+                record.updated = now;
+                changed = true;
+            }
+        }
+
+        for key in starred {
+            let star = GlobalStar {
+                session: session.to_string(),
+                key: key.clone(),
+            };
+            if !self.stars().any(|other| *other == star) {
+                self.set(star, true, now);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Return the starred panes in the order they were starred in.
+    pub fn stars(&self) -> impl Iterator<Item = &GlobalStar> {
+        self.records
+            .iter()
+            .filter(|record| record.starred)
+            .map(|record| &record.star)
+    }
+
+    /// Return the first star after current that is reachable, wrapping around at the end.
+    /// Without a current star, the search starts at the first star.
+    pub fn next<F>(&self, current: Option<&GlobalStar>, reachable: F) -> Option<&GlobalStar>
+    where
+        F: Fn(&GlobalStar) -> bool,
+    {
+        let stars: Vec<&GlobalStar> = self.stars().collect();
+        let start = current
+            .and_then(|current| stars.iter().position(|star| *star == current))
+            .map_or(0, |index| index + 1);
+
+        (0..stars.len())
+            .map(|offset| stars[(start + offset) % stars.len()])
+            .find(|star| reachable(star))
+    }
+
+    /// Return the first star before current that is reachable, wrapping around at the start.
+    /// Without a current star, the search starts at the last star.
+    pub fn previous<F>(&self, current: Option<&GlobalStar>, reachable: F) -> Option<&GlobalStar>
+    where
+        F: Fn(&GlobalStar) -> bool,
+    {
+        let stars: Vec<&GlobalStar> = self.stars().collect();
+        let len = stars.len();
+        let start = current
+            .and_then(|current| stars.iter().position(|star| *star == current))
+            .unwrap_or(len);
+
+        (1..=len)
+            .map(|offset| stars[(start + len - offset) % len])
+            .find(|star| reachable(star))
+    }
+
+    /// Merge the list read from the file into this one.
+    ///
+    /// The order of the file is kept and the panes that only this list has are
+    /// appended, so all sessions agree on the order after writing.
+    /// For a pane in both lists, the latest change wins and an unstar wins a tie.
+    /// Tombstones older than TOMBSTONE_TTL_MS are dropped.
+    pub fn merge(&mut self, other: GlobalStars, now: u64) {
+        let mut ours = std::mem::take(&mut self.records);
+
+        for theirs in other.records {
+            let record = match ours.iter().position(|record| record.star == theirs.star) {
+                Some(index) => {
+                    let ours = ours.remove(index);
+                    if (ours.updated, !ours.starred) > (theirs.updated, !theirs.starred) {
+                        ours
+                    } else {
+                        theirs
+                    }
+                }
+                None => theirs,
+            };
+            self.records.push(record);
+        }
+        self.records.append(&mut ours);
+
+        self.records.retain(|record| {
+            record.starred || now.saturating_sub(record.updated) < TOMBSTONE_TTL_MS
+        });
+    }
+
+    /// Serialize the list into the on-disk format:
+    ///
+    /// ```text
+    /// # zellij-pane-picker global stars v1
+    /// 1700000000000<TAB>+<TAB>web<TAB>terminal<TAB>Tab 1<TAB>vim
+    /// ```
+    ///
+    /// Each line holds the time of the last change, `+` for a starred or `-` for
+    /// an unstarred pane, the session, the pane kind, the tab name, and the pane title
+    /// separated by tabs and escaped like the stars file.
+    pub fn encode(&self) -> String {
+        let mut out = String::from(FORMAT_HEADER);
+        out.push('\n');
+
+        for record in &self.records {
+            let state = if record.starred {
+                STATE_STARRED
+            } else {
+                STATE_UNSTARRED
+            };
+            let kind = if record.star.key.is_plugin {
+                star::KIND_PLUGIN
+            } else {
+                star::KIND_TERMINAL
+            };
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\n",
+                record.updated,
+                state,
+                star::escape(&record.star.session),
+                kind,
+                star::escape(&record.star.key.tab_name),
+                star::escape(&record.star.key.title)
+            ));
+        }
+
+        out
+    }
+
+    /// Parse the on-disk format written by encode.
+    /// Blank lines, comments, and malformed lines are skipped.
+    pub fn decode(contents: &str) -> Self {
+        let records = contents
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                let [updated, state, session, kind, tab_name, title] = fields[..] else {
+                    return None;
+                };
+
+                let starred = match state {
+                    STATE_STARRED => true,
+                    STATE_UNSTARRED => false,
+                    _ => return None,
+                };
+                let is_plugin = match kind {
+                    star::KIND_TERMINAL => false,
+                    star::KIND_PLUGIN => true,
+                    _ => return None,
+                };
+
+                Some(Record {
+                    star: GlobalStar {
+                        session: star::unescape(session),
+                        key: PaneKey {
+                            is_plugin,
+                            tab_name: star::unescape(tab_name),
+                            title: star::unescape(title),
+                        },
+                    },
+                    starred,
+                    updated: updated.parse().ok()?,
+                })
+            })
+            .collect();
+
+        Self { records }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn star(session: &str, title: &str) -> GlobalStar {
+        GlobalStar {
+            session: session.to_string(),
+            key: PaneKey {
+                is_plugin: false,
+                tab_name: String::from("Tab"),
+                title: title.to_string(),
+            },
+        }
+    }
+
+    fn stars(records: &[(&str, &str, bool, u64)]) -> GlobalStars {
+        GlobalStars {
+            records: records
+                .iter()
+                .map(|(session, title, starred, updated)| Record {
+                    star: star(session, title),
+                    starred: *starred,
+                    updated: *updated,
+                })
+                .collect(),
+        }
+    }
+
+    fn by_title(global: &GlobalStars, title: &str) -> GlobalStar {
+        global
+            .stars()
+            .find(|star| star.key.title == title)
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn set() {
+        let mut global = GlobalStars::default();
+
+        global.set(star("web", "vim"), true, 1);
+        global.set(star("api", "logs"), true, 2);
+        global.set(star("web", "vim"), false, 3);
+
+        assert_eq!(global.stars().collect::<Vec<_>>(), [&star("api", "logs")]);
+        assert_eq!(
+            global,
+            stars(&[("web", "vim", false, 3), ("api", "logs", true, 2)])
+        );
+    }
+
+    #[test]
+    fn sync_session() {
+        let mut global = stars(&[
+            ("web", "vim", true, 1),
+            ("web", "old title", true, 1),
+            ("web", "restoring", true, 1),
+            ("web", "unstarred", false, 1),
+            ("api", "logs", true, 1),
+        ]);
+        let key = |title: &str| star("web", title).key;
+
+        let changed = global.sync_session(
+            "web",
+            &[key("vim"), key("new title")],
+            |key| key.title == "restoring",
+            2,
+        );
+
+        assert!(changed);
+        assert_eq!(
+            global,
+            stars(&[
+                ("web", "vim", true, 1),
+                ("web", "old title", false, 2),
+                ("web", "restoring", true, 1),
+                ("web", "unstarred", false, 1),
+                ("api", "logs", true, 1),
+                ("web", "new title", true, 2),
+            ])
+        );
+
+        let changed = global.sync_session("web", &[key("vim"), key("new title")], |_| true, 3);
+
+        assert!(!changed);
+    }
+
+    #[rstest]
+    #[case::newer_star_wins(
+        &[("web", "vim", false, 1)],
+        &[("web", "vim", true, 2)],
+        &[("web", "vim", true, 2)]
+    )]
+    #[case::newer_unstar_wins(
+        &[("web", "vim", false, 3)],
+        &[("web", "vim", true, 2)],
+        &[("web", "vim", false, 3)]
+    )]
+    #[case::unstar_wins_tie(
+        &[("web", "vim", true, 2)],
+        &[("web", "vim", false, 2)],
+        &[("web", "vim", false, 2)]
+    )]
+    #[case::file_order_first(
+        &[("web", "vim", true, 5), ("api", "logs", true, 1)],
+        &[("api", "logs", true, 1), ("docs", "mdbook", true, 4)],
+        &[("api", "logs", true, 1), ("docs", "mdbook", true, 4), ("web", "vim", true, 5)]
+    )]
+    fn merge(
+        #[case] ours: &[(&str, &str, bool, u64)],
+        #[case] theirs: &[(&str, &str, bool, u64)],
+        #[case] expected: &[(&str, &str, bool, u64)],
+    ) {
+        let mut global = stars(ours);
+
+        global.merge(stars(theirs), 10);
+
+        assert_eq!(global, stars(expected));
+    }
+
+    #[test]
+    fn merge_drops_old_tombstones() {
+        let mut global = stars(&[("web", "vim", false, 10)]);
+
+        global.merge(
+            stars(&[("api", "logs", false, 0), ("docs", "mdbook", true, 0)]),
+            TOMBSTONE_TTL_MS + 5,
+        );
+
+        assert_eq!(
+            global,
+            stars(&[("docs", "mdbook", true, 0), ("web", "vim", false, 10)])
+        );
+    }
+
+    #[test]
+    fn merge_concurrent_changes() {
+        // Two sessions start from the same file and change different panes.
+        let file = stars(&[("web", "vim", true, 1), ("api", "logs", true, 1)]);
+        let mut web = stars(&[("web", "vim", true, 1), ("api", "logs", true, 1)]);
+        let mut api = stars(&[("web", "vim", true, 1), ("api", "logs", true, 1)]);
+        web.set(star("web", "vim"), false, 2);
+        api.set(star("api", "tests"), true, 3);
+
+        // The web session writes first, the api session merges its write.
+        web.merge(file, 4);
+        api.merge(GlobalStars::decode(&web.encode()), 4);
+
+        assert_eq!(
+            api,
+            stars(&[
+                ("web", "vim", false, 2),
+                ("api", "logs", true, 1),
+                ("api", "tests", true, 3)
+            ])
+        );
+    }
+
+    #[rstest]
+    #[case::from_none(None, |_: &GlobalStar| true, Some("vim"))]
+    #[case::after_current(Some("vim"), |_: &GlobalStar| true, Some("logs"))]
+    #[case::wrap_around(Some("tests"), |_: &GlobalStar| true, Some("vim"))]
+    #[case::skip_unreachable(Some("vim"), |s: &GlobalStar| s.session != "api", Some("tests"))]
+    #[case::none_reachable(None, |_: &GlobalStar| false, None)]
+    fn next(
+        #[case] current: Option<&str>,
+        #[case] reachable: fn(&GlobalStar) -> bool,
+        #[case] expected: Option<&str>,
+    ) {
+        let global = stars(&[
+            ("web", "vim", true, 1),
+            ("web", "old", false, 1),
+            ("api", "logs", true, 1),
+            ("docs", "tests", true, 1),
+        ]);
+        let current = current.map(|title| by_title(&global, title));
+
+        let got = global.next(current.as_ref(), reachable);
+
+        assert_eq!(got.map(|star| star.key.title.as_str()), expected);
+    }
+
+    #[rstest]
+    #[case::from_none(None, Some("tests"))]
+    #[case::before_current(Some("logs"), Some("vim"))]
+    #[case::wrap_around(Some("vim"), Some("tests"))]
+    fn previous(#[case] current: Option<&str>, #[case] expected: Option<&str>) {
+        let global = stars(&[
+            ("web", "vim", true, 1),
+            ("api", "logs", true, 1),
+            ("docs", "tests", true, 1),
+        ]);
+        let current = current.map(|title| by_title(&global, title));
+
+        let got = global.previous(current.as_ref(), |_| true);
+
+        assert_eq!(got.map(|star| star.key.title.as_str()), expected);
+    }
+
+    #[test]
+    fn previous_without_stars() {
+        assert_eq!(GlobalStars::default().previous(None, |_| true), None);
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let global = GlobalStars {
+            records: vec![Record {
+                star: GlobalStar {
+                    session: String::from("web\tshop"),
+                    key: PaneKey {
+                        is_plugin: true,
+                        tab_name: String::from("Tab 1"),
+                        title: String::from("filepicker"),
+                    },
+                },
+                starred: false,
+                updated: 1700000000000,
+            }],
+        };
+
+        let contents = global.encode();
+
+        assert_eq!(
+            contents,
+            "# zellij-pane-picker global stars v1\n\
+             1700000000000\t-\tweb\\tshop\tplugin\tTab 1\tfilepicker\n"
+        );
+        assert_eq!(
+            GlobalStars::decode(&format!(
+                "{}\nbad line\nx\t+\ta\tterminal\tb\tc\n",
+                contents
+            )),
+            global
+        );
+    }
+}
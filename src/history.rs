@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+
+/// The number of queries remembered by default.
+pub const DEFAULT_CAPACITY: usize = 100;
+
+/// A bounded history of submitted search queries.
+///
+/// Recalling walks from the most recent query to older ones like a shell
+/// history. The query that was being typed before the walk started is
+/// given back when walking past the most recent query again.
+pub struct SearchHistory {
+    // The most recently submitted query is at the front.
+    entries: VecDeque<String>,
+    capacity: usize,
+
+    // Index of the recalled query in entries, None while not walking.
+    cursor: Option<usize>,
+    draft: String,
+}
+
+impl Default for SearchHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl SearchHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            cursor: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Return the most recently submitted query.
+    pub fn last(&self) -> Option<&str> {
+        self.entries.front().map(String::as_str)
+    }
+
+    /// Record a submitted query and end the walk.
+    /// Blank queries are ignored and repeated queries are moved to the front.
+    pub fn push(&mut self, query: &str) {
+        self.reset();
+
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|entry| entry != query);
+        self.entries.push_front(query.to_string());
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Step to the next older query and return it.
+    /// current is the query being typed, it is kept when the walk starts.
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let index = match self.cursor {
+            None => 0,
+            Some(index) => index + 1,
+        };
+        if index >= self.entries.len() {
+            return None;
+        }
+
+        if self.cursor.is_none() {
+            self.draft = current.to_string();
+        }
+        self.cursor = Some(index);
+        self.entries.get(index).map(String::as_str)
+    }
+
+    /// Step to the next newer query and return it,
+    /// or the query that was typed before the walk when there is none.
+    pub fn next(&mut self) -> Option<&str> {
+        match self.cursor? {
+            0 => {
+                self.cursor = None;
+                Some(&self.draft)
+            }
+            index => {
+                self.cursor = Some(index - 1);
+                self.entries.get(index - 1).map(String::as_str)
+            }
+        }
+    }
+
+    /// End the walk without recording a query.
+    pub fn reset(&mut self) {
+        self.cursor = None;
+        self.draft.clear();
+    }
+
+    /// Serialize the queries, one per line with the most recent first.
+    pub fn encode(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect()
+    }
+
+    /// Replace the queries with the ones serialized by encode.
+    pub fn decode(&mut self, contents: &str) {
+        self.entries = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .take(self.capacity)
+            .map(String::from)
+            .collect();
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(entries: &[&str]) -> SearchHistory {
+        SearchHistory {
+            entries: entries.iter().map(|e| e.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn push() {
+        let mut history = history(&["tab:api", "logs"]);
+
+        history.push("  ");
+        history.push(" logs ");
+        history.push("vim");
+
+        assert_eq!(history.entries, ["vim", "logs", "tab:api"]);
+        assert_eq!(history.last(), Some("vim"));
+    }
+
+    #[test]
+    fn push_is_bounded() {
+        let mut history = SearchHistory::new(2);
+
+        for query in ["a", "b", "c"] {
+            history.push(query);
+        }
+
+        assert_eq!(history.entries, ["c", "b"]);
+    }
+
+    #[test]
+    fn walk() {
+        let mut history = history(&["c", "b", "a"]);
+
+        assert_eq!(history.next(), None);
+        assert_eq!(history.previous("typed"), Some("c"));
+        assert_eq!(history.previous("c"), Some("b"));
+        assert_eq!(history.previous("b"), Some("a"));
+        assert_eq!(history.previous("a"), None);
+        assert_eq!(history.next(), Some("b"));
+        assert_eq!(history.next(), Some("c"));
+        assert_eq!(history.next(), Some("typed"));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn walk_empty() {
+        let mut history = SearchHistory::default();
+
+        assert_eq!(history.previous("typed"), None);
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn push_ends_walk() {
+        let mut history = history(&["b", "a"]);
+        history.previous("");
+        history.previous("");
+
+        history.push("a");
+
+        assert_eq!(history.previous(""), Some("a"));
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let history = history(&["tab:api logs", "vim"]);
+        let contents = history.encode();
+
+        let mut decoded = SearchHistory::new(1);
+        decoded.decode(&format!("\n{}", contents));
+
+        assert_eq!(contents, "tab:api logs\nvim\n");
+        assert_eq!(decoded.entries, ["tab:api logs"]);
+    }
+}
@@ -1,5 +1,4 @@
 use std::collections::BTreeMap;
-use std::convert::TryFrom;
 use std::str::FromStr;
 use thiserror::Error;
 use zellij_tile::prelude::BareKey;
@@ -7,23 +6,79 @@ use zellij_tile::prelude::InputMode;
 use zellij_tile::prelude::KeyWithModifier;
 
 pub const LIST_PANES: &str = "list_panes";
+pub const LIST_TABS: &str = "list_tabs";
+pub const LIST_SESSIONS: &str = "list_sessions";
 pub const NAVIGATE_BACK: &str = "navigate_back";
+pub const FOCUS_BACK: &str = "focus_back";
+pub const FOCUS_FORWARD: &str = "focus_forward";
 pub const TOGGLE_STAR: &str = "toggle_star";
 pub const PREV_STAR: &str = "previous_star";
 pub const NEXT_STAR: &str = "next_star";
+pub const NEXT_STAR_GROUP: &str = "next_star_group";
+pub const PREV_STAR_GROUP: &str = "previous_star_group";
+pub const JUMP: &str = "jump";
+pub const FOCUS_SLOTS: [&str; 9] = [
+    "focus_slot_1",
+    "focus_slot_2",
+    "focus_slot_3",
+    "focus_slot_4",
+    "focus_slot_5",
+    "focus_slot_6",
+    "focus_slot_7",
+    "focus_slot_8",
+    "focus_slot_9",
+];
+
+const JUMP_KEYS: &str = "jump_keys";
 
 const PLUGIN_SELECT_DOWN: &str = "plugin_select_down";
 const PLUGIN_SELECT_UP: &str = "plugin_select_up";
 const PLUGIN_NAVIGATE_TO: &str = "plugin_navigate_to";
 const PLUGIN_HIDE: &str = "plugin_hide";
 const PLUGIN_TOGGLE_STAR: &str = "plugin_toggle_star";
+const PLUGIN_MOVE_STAR_UP: &str = "plugin_move_star_up";
+const PLUGIN_MOVE_STAR_DOWN: &str = "plugin_move_star_down";
+const PLUGIN_TOGGLE_PREVIEW: &str = "plugin_toggle_preview";
+const PLUGIN_PAGE_DOWN: &str = "plugin_page_down";
+const PLUGIN_PAGE_UP: &str = "plugin_page_up";
+const PLUGIN_SELECT_FIRST: &str = "plugin_select_first";
+const PLUGIN_SELECT_LAST: &str = "plugin_select_last";
+const PLUGIN_CURSOR_LEFT: &str = "plugin_cursor_left";
+const PLUGIN_CURSOR_RIGHT: &str = "plugin_cursor_right";
+const PLUGIN_CURSOR_HOME: &str = "plugin_cursor_home";
+const PLUGIN_CURSOR_END: &str = "plugin_cursor_end";
+const PLUGIN_DELETE_BACKWARD: &str = "plugin_delete_backward";
+const PLUGIN_DELETE_FORWARD: &str = "plugin_delete_forward";
+const PLUGIN_DELETE_WORD: &str = "plugin_delete_word";
+const PLUGIN_CLEAR_SEARCH: &str = "plugin_clear_search";
+const PLUGIN_HISTORY_PREVIOUS: &str = "plugin_history_previous";
+const PLUGIN_HISTORY_NEXT: &str = "plugin_history_next";
+const PLUGIN_TOGGLE_MARK: &str = "plugin_toggle_mark";
+const PLUGIN_CLOSE_PANES: &str = "plugin_close_panes";
+const PLUGIN_TOGGLE_FLOATING: &str = "plugin_toggle_floating";
+const PLUGIN_BREAK_OUT: &str = "plugin_break_out";
+const PLUGIN_RENAME_PANES: &str = "plugin_rename_panes";
+const PLUGIN_MOVE_TO_TAB: &str = "plugin_move_to_tab";
+const PLUGIN_TOGGLE_FULLSCREEN: &str = "plugin_toggle_fullscreen";
+const PLUGIN_SWITCH_MODE: &str = "plugin_switch_mode";
+const PLUGIN_NEW_TAB: &str = "plugin_new_tab";
+const PLUGIN_COLLAPSE: &str = "plugin_collapse";
+const PLUGIN_EXPAND: &str = "plugin_expand";
 
 pub struct Keybinds {
     list_panes: Option<KeyWithModifier>,
+    list_tabs: Option<KeyWithModifier>,
+    list_sessions: Option<KeyWithModifier>,
     navigate_back: Option<KeyWithModifier>,
+    focus_back: Option<KeyWithModifier>,
+    focus_forward: Option<KeyWithModifier>,
     toggle_star: Option<KeyWithModifier>,
     next_star: Option<KeyWithModifier>,
     previous_star: Option<KeyWithModifier>,
+    next_star_group: Option<KeyWithModifier>,
+    previous_star_group: Option<KeyWithModifier>,
+    focus_slots: [Option<KeyWithModifier>; 9],
+    jumps: Vec<(KeyWithModifier, String)>, // Keys that jump to the pane matching a query.
 
     // These are key bindings while inside the plugin pane.
     pub plugin_select_down: Option<KeyWithModifier>,
@@ -31,22 +86,104 @@ pub struct Keybinds {
     pub plugin_navigate_to: Option<KeyWithModifier>,
     pub plugin_hide: Option<KeyWithModifier>,
     pub plugin_toggle_star: Option<KeyWithModifier>,
+    pub plugin_move_star_up: Option<KeyWithModifier>,
+    pub plugin_move_star_down: Option<KeyWithModifier>,
+    pub plugin_toggle_preview: Option<KeyWithModifier>,
+    pub plugin_page_down: Option<KeyWithModifier>,
+    pub plugin_page_up: Option<KeyWithModifier>,
+    pub plugin_select_first: Option<KeyWithModifier>,
+    pub plugin_select_last: Option<KeyWithModifier>,
+
+    // These edit the search prompt.
+    pub plugin_cursor_left: Option<KeyWithModifier>,
+    pub plugin_cursor_right: Option<KeyWithModifier>,
+    pub plugin_cursor_home: Option<KeyWithModifier>,
+    pub plugin_cursor_end: Option<KeyWithModifier>,
+    pub plugin_delete_backward: Option<KeyWithModifier>,
+    pub plugin_delete_forward: Option<KeyWithModifier>,
+    pub plugin_delete_word: Option<KeyWithModifier>,
+    pub plugin_clear_search: Option<KeyWithModifier>,
+    pub plugin_history_previous: Option<KeyWithModifier>,
+    pub plugin_history_next: Option<KeyWithModifier>,
+    pub plugin_toggle_mark: Option<KeyWithModifier>,
+    pub plugin_toggle_fullscreen: Option<KeyWithModifier>,
+
+    // These act on the marked panes, or the selected pane when none are marked.
+    pub plugin_close_panes: Option<KeyWithModifier>,
+    pub plugin_toggle_floating: Option<KeyWithModifier>,
+    pub plugin_break_out: Option<KeyWithModifier>,
+    pub plugin_rename_panes: Option<KeyWithModifier>,
+    pub plugin_move_to_tab: Option<KeyWithModifier>,
+
+    pub plugin_switch_mode: Option<KeyWithModifier>,
+    pub plugin_new_tab: Option<KeyWithModifier>,
+    pub plugin_collapse: Option<KeyWithModifier>,
+    pub plugin_expand: Option<KeyWithModifier>,
 }
 
 impl Default for Keybinds {
     fn default() -> Keybinds {
         Keybinds {
             list_panes: Some(KeyWithModifier::new(BareKey::Char('y')).with_alt_modifier()),
+            list_tabs: Some(KeyWithModifier::new(BareKey::Char('t')).with_alt_modifier()),
+            list_sessions: Some(KeyWithModifier::new(BareKey::Char('s')).with_alt_modifier()),
             navigate_back: Some(KeyWithModifier::new(BareKey::Char('o')).with_alt_modifier()),
+            focus_back: Some(KeyWithModifier::new(BareKey::Char(',')).with_alt_modifier()),
+            focus_forward: Some(KeyWithModifier::new(BareKey::Char('.')).with_alt_modifier()),
             toggle_star: Some(KeyWithModifier::new(BareKey::Char('l')).with_alt_modifier()),
             next_star: Some(KeyWithModifier::new(BareKey::Char('i')).with_alt_modifier()),
             previous_star: Some(KeyWithModifier::new(BareKey::Char('u')).with_alt_modifier()),
+            next_star_group: Some(KeyWithModifier::new(BareKey::Char('g')).with_alt_modifier()),
+            previous_star_group: None,
+            focus_slots: ['1', '2', '3', '4', '5', '6', '7', '8', '9']
+                .map(|c| Some(KeyWithModifier::new(BareKey::Char(c)).with_alt_modifier())),
+            jumps: Vec::new(),
 
             plugin_select_down: Some(KeyWithModifier::new(BareKey::Down)),
             plugin_select_up: Some(KeyWithModifier::new(BareKey::Up)),
             plugin_navigate_to: Some(KeyWithModifier::new(BareKey::Enter)),
             plugin_hide: Some(KeyWithModifier::new(BareKey::Esc)),
-            plugin_toggle_star: Some(KeyWithModifier::new(BareKey::Char(' '))),
+            plugin_toggle_star: Some(KeyWithModifier::new(BareKey::Char('s')).with_ctrl_modifier()),
+            plugin_move_star_up: Some(KeyWithModifier::new(BareKey::Up).with_ctrl_modifier()),
+            plugin_move_star_down: Some(KeyWithModifier::new(BareKey::Down).with_ctrl_modifier()),
+            plugin_toggle_preview: Some(
+                KeyWithModifier::new(BareKey::Char('v')).with_ctrl_modifier(),
+            ),
+            plugin_page_down: Some(KeyWithModifier::new(BareKey::PageDown)),
+            plugin_page_up: Some(KeyWithModifier::new(BareKey::PageUp)),
+            plugin_select_first: Some(KeyWithModifier::new(BareKey::Home).with_ctrl_modifier()),
+            plugin_select_last: Some(KeyWithModifier::new(BareKey::End).with_ctrl_modifier()),
+
+            plugin_cursor_left: Some(KeyWithModifier::new(BareKey::Left)),
+            plugin_cursor_right: Some(KeyWithModifier::new(BareKey::Right)),
+            plugin_cursor_home: Some(KeyWithModifier::new(BareKey::Home)),
+            plugin_cursor_end: Some(KeyWithModifier::new(BareKey::End)),
+            plugin_delete_backward: Some(KeyWithModifier::new(BareKey::Backspace)),
+            plugin_delete_forward: Some(KeyWithModifier::new(BareKey::Delete)),
+            plugin_delete_word: Some(KeyWithModifier::new(BareKey::Char('w')).with_ctrl_modifier()),
+            plugin_clear_search: Some(
+                KeyWithModifier::new(BareKey::Char('u')).with_ctrl_modifier(),
+            ),
+            plugin_history_previous: Some(KeyWithModifier::new(BareKey::Up).with_shift_modifier()),
+            plugin_history_next: Some(KeyWithModifier::new(BareKey::Down).with_shift_modifier()),
+
+            plugin_toggle_mark: Some(KeyWithModifier::new(BareKey::Tab)),
+            plugin_close_panes: Some(KeyWithModifier::new(BareKey::Char('x')).with_ctrl_modifier()),
+            plugin_toggle_floating: Some(
+                KeyWithModifier::new(BareKey::Char('e')).with_ctrl_modifier(),
+            ),
+            plugin_break_out: Some(KeyWithModifier::new(BareKey::Char('z')).with_ctrl_modifier()),
+            plugin_rename_panes: Some(
+                KeyWithModifier::new(BareKey::Char('r')).with_ctrl_modifier(),
+            ),
+            plugin_move_to_tab: Some(KeyWithModifier::new(BareKey::Char('y')).with_ctrl_modifier()),
+            plugin_toggle_fullscreen: Some(
+                KeyWithModifier::new(BareKey::Char('f')).with_ctrl_modifier(),
+            ),
+            plugin_switch_mode: Some(KeyWithModifier::new(BareKey::Char('l')).with_ctrl_modifier()),
+            plugin_new_tab: Some(KeyWithModifier::new(BareKey::Char('a')).with_ctrl_modifier()),
+            plugin_collapse: Some(KeyWithModifier::new(BareKey::Left).with_ctrl_modifier()),
+            plugin_expand: Some(KeyWithModifier::new(BareKey::Right).with_ctrl_modifier()),
         }
     }
 }
@@ -58,20 +195,34 @@ impl Keybinds {
     {
         let key_actions = [
             (&self.list_panes, LIST_PANES),
+            (&self.list_tabs, LIST_TABS),
+            (&self.list_sessions, LIST_SESSIONS),
             (&self.navigate_back, NAVIGATE_BACK),
+            (&self.focus_back, FOCUS_BACK),
+            (&self.focus_forward, FOCUS_FORWARD),
             (&self.toggle_star, TOGGLE_STAR),
             (&self.next_star, NEXT_STAR),
             (&self.previous_star, PREV_STAR),
+            (&self.next_star_group, NEXT_STAR_GROUP),
+            (&self.previous_star_group, PREV_STAR_GROUP),
         ];
+        let slot_actions = self.focus_slots.iter().zip(FOCUS_SLOTS);
 
-        for (key, action) in key_actions {
+        for (key, action) in IntoIterator::into_iter(key_actions).chain(slot_actions) {
             if let Some(key) = key {
                 configure(
-                    create_keybind_config(base_mode, plugin_id, key, action),
+                    create_keybind_config(base_mode, plugin_id, key, action, None),
                     false,
                 );
             }
         }
+
+        for (key, query) in &self.jumps {
+            configure(
+                create_keybind_config(base_mode, plugin_id, key, JUMP, Some(query)),
+                false,
+            );
+        }
     }
 }
 
@@ -79,53 +230,144 @@ impl Keybinds {
 pub enum KeybindError {
     #[error(transparent)]
     FromStr(#[from] Box<dyn std::error::Error>),
+    #[error("invalid jump {0:?}, expected <key>=<query>")]
+    InvalidJump(String),
+    #[error("invalid key {value:?} for {key}")]
+    InvalidKey { key: &'static str, value: String },
 }
 
-impl TryFrom<BTreeMap<String, String>> for Keybinds {
-    type Error = KeybindError;
-    fn try_from(map: BTreeMap<String, String>) -> Result<Self, Self::Error> {
+impl Keybinds {
+    /// Parse the key bindings option by option.
+    /// An invalid key keeps its default binding and yields an error,
+    /// while the other bindings still apply.
+    pub fn parse(map: &BTreeMap<String, String>) -> (Self, Vec<KeybindError>) {
         let mut keybinds = Keybinds::default();
+        let mut errors = Vec::new();
 
+        let slot_mappings = FOCUS_SLOTS
+            .iter()
+            .copied()
+            .zip(keybinds.focus_slots.iter_mut());
         let key_mappings = [
             (PLUGIN_SELECT_DOWN, &mut keybinds.plugin_select_down),
             (PLUGIN_SELECT_UP, &mut keybinds.plugin_select_up),
             (PLUGIN_NAVIGATE_TO, &mut keybinds.plugin_navigate_to),
             (PLUGIN_HIDE, &mut keybinds.plugin_hide),
             (PLUGIN_TOGGLE_STAR, &mut keybinds.plugin_toggle_star),
+            (PLUGIN_MOVE_STAR_UP, &mut keybinds.plugin_move_star_up),
+            (PLUGIN_MOVE_STAR_DOWN, &mut keybinds.plugin_move_star_down),
+            (PLUGIN_TOGGLE_PREVIEW, &mut keybinds.plugin_toggle_preview),
+            (PLUGIN_PAGE_DOWN, &mut keybinds.plugin_page_down),
+            (PLUGIN_PAGE_UP, &mut keybinds.plugin_page_up),
+            (PLUGIN_SELECT_FIRST, &mut keybinds.plugin_select_first),
+            (PLUGIN_SELECT_LAST, &mut keybinds.plugin_select_last),
+            (PLUGIN_CURSOR_LEFT, &mut keybinds.plugin_cursor_left),
+            (PLUGIN_CURSOR_RIGHT, &mut keybinds.plugin_cursor_right),
+            (PLUGIN_CURSOR_HOME, &mut keybinds.plugin_cursor_home),
+            (PLUGIN_CURSOR_END, &mut keybinds.plugin_cursor_end),
+            (PLUGIN_DELETE_BACKWARD, &mut keybinds.plugin_delete_backward),
+            (PLUGIN_DELETE_FORWARD, &mut keybinds.plugin_delete_forward),
+            (PLUGIN_DELETE_WORD, &mut keybinds.plugin_delete_word),
+            (PLUGIN_CLEAR_SEARCH, &mut keybinds.plugin_clear_search),
+            (
+                PLUGIN_HISTORY_PREVIOUS,
+                &mut keybinds.plugin_history_previous,
+            ),
+            (PLUGIN_HISTORY_NEXT, &mut keybinds.plugin_history_next),
+            (PLUGIN_TOGGLE_MARK, &mut keybinds.plugin_toggle_mark),
+            (PLUGIN_CLOSE_PANES, &mut keybinds.plugin_close_panes),
+            (PLUGIN_TOGGLE_FLOATING, &mut keybinds.plugin_toggle_floating),
+            (PLUGIN_BREAK_OUT, &mut keybinds.plugin_break_out),
+            (PLUGIN_RENAME_PANES, &mut keybinds.plugin_rename_panes),
+            (PLUGIN_MOVE_TO_TAB, &mut keybinds.plugin_move_to_tab),
+            (
+                PLUGIN_TOGGLE_FULLSCREEN,
+                &mut keybinds.plugin_toggle_fullscreen,
+            ),
+            (PLUGIN_SWITCH_MODE, &mut keybinds.plugin_switch_mode),
+            (PLUGIN_NEW_TAB, &mut keybinds.plugin_new_tab),
+            (PLUGIN_COLLAPSE, &mut keybinds.plugin_collapse),
+            (PLUGIN_EXPAND, &mut keybinds.plugin_expand),
             (LIST_PANES, &mut keybinds.list_panes),
+            (LIST_TABS, &mut keybinds.list_tabs),
+            (LIST_SESSIONS, &mut keybinds.list_sessions),
             (NAVIGATE_BACK, &mut keybinds.navigate_back),
+            (FOCUS_BACK, &mut keybinds.focus_back),
+            (FOCUS_FORWARD, &mut keybinds.focus_forward),
             (TOGGLE_STAR, &mut keybinds.toggle_star),
             (PREV_STAR, &mut keybinds.previous_star),
             (NEXT_STAR, &mut keybinds.next_star),
+            (NEXT_STAR_GROUP, &mut keybinds.next_star_group),
+            (PREV_STAR_GROUP, &mut keybinds.previous_star_group),
         ];
 
-        for (key_name, keybind_field) in key_mappings {
+        for (key_name, keybind_field) in IntoIterator::into_iter(key_mappings).chain(slot_mappings)
+        {
             if let Some(key_str) = map.get(key_name) {
-                if !key_str.is_empty() {
-                    *keybind_field = Some(KeyWithModifier::from_str(key_str)?);
-                } else {
+                if key_str.is_empty() {
                     *keybind_field = None;
+                } else {
+                    match KeyWithModifier::from_str(key_str) {
+                        Ok(key) => *keybind_field = Some(key),
+                        Err(_) => errors.push(KeybindError::InvalidKey {
+                            key: key_name,
+                            value: key_str.to_string(),
+                        }),
+                    }
                 }
             }
         }
 
-        Ok(keybinds)
+        if let Some(value) = map.get(JUMP_KEYS) {
+            match parse_jumps(value) {
+                Ok(jumps) => keybinds.jumps = jumps,
+                Err(err) => errors.push(err),
+            }
+        }
+
+        (keybinds, errors)
     }
 }
 
+/// Parse a comma-separated list of jumps like `Alt p=psql, Alt L=tab:api logs`.
+fn parse_jumps(value: &str) -> Result<Vec<(KeyWithModifier, String)>, KeybindError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|jump| !jump.is_empty())
+        .map(|jump| {
+            let (key, query) = jump
+                .split_once('=')
+                .filter(|(_, query)| !query.trim().is_empty())
+                .ok_or_else(|| KeybindError::InvalidJump(jump.to_string()))?;
+            Ok((
+                KeyWithModifier::from_str(key.trim())?,
+                query.trim().to_string(),
+            ))
+        })
+        .collect()
+}
+
 pub fn create_keybind_config(
     mode: InputMode,
     plugin_id: u32,
     key: &KeyWithModifier,
     message_plugin_id: &str,
+    payload: Option<&str>,
 ) -> String {
+    let payload = payload.map_or(String::new(), |payload| {
+        format!(
+            "\n                        payload \"{}\"",
+            payload.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    });
     format!(
         "
         keybinds {{
             {:?} {{
                 bind \"{}\" {{
                     MessagePluginId {} {{
-                        name \"{}\"
+                        name \"{}\"{}
                     }}
                 }}
             }}
@@ -134,7 +376,8 @@ pub fn create_keybind_config(
         format!("{:?}", mode).to_lowercase(),
         key,
         plugin_id,
-        message_plugin_id
+        message_plugin_id,
+        payload
     )
 }
 
@@ -146,8 +389,14 @@ mod tests {
     fn test_bind_global_keys() {
         let mut keybinds = Keybinds {
             toggle_star: None,
+            focus_slots: Default::default(),
+            jumps: vec![(
+                KeyWithModifier::new(BareKey::Char('p')).with_alt_modifier(),
+                String::from("title:\"db\" psql"),
+            )],
             ..Default::default()
         };
+        keybinds.focus_slots[2] = Some(KeyWithModifier::new(BareKey::F(3)));
         let base_mode = InputMode::Normal;
         let plugin_id = 42;
         let mut got_configs = Vec::new();
@@ -160,15 +409,22 @@ mod tests {
     }
 
     #[test]
-    fn test_try_from() {
+    fn test_parse() {
         let map = BTreeMap::from([
             (PLUGIN_SELECT_DOWN.to_string(), String::from("Ctrl Down")),
             (PLUGIN_HIDE.to_string(), String::from("")),
+            (
+                PLUGIN_DELETE_WORD.to_string(),
+                String::from("Alt Backspace"),
+            ),
+            (FOCUS_SLOTS[0].to_string(), String::from("")),
+            (FOCUS_SLOTS[1].to_string(), String::from("Ctrl 2")),
             (String::from("unknown_key"), String::from("Invalid")),
         ]);
 
-        let keybinds = Keybinds::try_from(map).unwrap();
+        let (keybinds, errors) = Keybinds::parse(&map);
 
+        assert!(errors.is_empty());
         assert_eq!(
             keybinds.plugin_select_down,
             Some(KeyWithModifier::new(BareKey::Down).with_ctrl_modifier()),
@@ -178,5 +434,83 @@ mod tests {
             Some(KeyWithModifier::new(BareKey::Up),)
         );
         assert_eq!(keybinds.plugin_hide, None);
+        assert_eq!(
+            keybinds.plugin_delete_word,
+            Some(KeyWithModifier::new(BareKey::Backspace).with_alt_modifier()),
+        );
+        assert_eq!(keybinds.focus_slots[0], None);
+        assert_eq!(
+            keybinds.focus_slots[1],
+            Some(KeyWithModifier::new(BareKey::Char('2')).with_ctrl_modifier()),
+        );
+        assert_eq!(
+            keybinds.focus_slots[2],
+            Some(KeyWithModifier::new(BareKey::Char('3')).with_alt_modifier()),
+        );
+    }
+
+    #[test]
+    fn test_parse_jump_keys() {
+        let map = BTreeMap::from([(
+            JUMP_KEYS.to_string(),
+            String::from("Alt p=psql, ,Ctrl Alt l = tab:api logs"),
+        )]);
+
+        let (keybinds, errors) = Keybinds::parse(&map);
+
+        assert!(errors.is_empty());
+        assert_eq!(
+            keybinds.jumps,
+            vec![
+                (
+                    KeyWithModifier::new(BareKey::Char('p')).with_alt_modifier(),
+                    String::from("psql")
+                ),
+                (
+                    KeyWithModifier::new(BareKey::Char('l'))
+                        .with_ctrl_modifier()
+                        .with_alt_modifier(),
+                    String::from("tab:api logs")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_jump_keys() {
+        for value in ["Alt p", "Alt p= ", "NoSuchKey=psql"] {
+            let map = BTreeMap::from([(JUMP_KEYS.to_string(), value.to_string())]);
+
+            let (keybinds, errors) = Keybinds::parse(&map);
+
+            assert_eq!(errors.len(), 1, "{}", value);
+            assert!(keybinds.jumps.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_parse_keeps_valid_keys() {
+        let map = BTreeMap::from([
+            (PLUGIN_HIDE.to_string(), String::from("NoSuchKey")),
+            (PLUGIN_SELECT_DOWN.to_string(), String::from("Ctrl j")),
+            (LIST_PANES.to_string(), String::from("Alt Nope")),
+        ]);
+
+        let (keybinds, errors) = Keybinds::parse(&map);
+
+        // Every invalid key is reported and keeps its default binding.
+        assert_eq!(
+            errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "invalid key \"NoSuchKey\" for plugin_hide",
+                "invalid key \"Alt Nope\" for list_panes",
+            ]
+        );
+        assert_eq!(keybinds.plugin_hide, Keybinds::default().plugin_hide);
+        assert_eq!(keybinds.list_panes, Keybinds::default().list_panes);
+        assert_eq!(
+            keybinds.plugin_select_down,
+            Some(KeyWithModifier::new(BareKey::Char('j')).with_ctrl_modifier()),
+        );
     }
 }
@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+/// A pane as printed by the list-panes command.
+#[derive(Debug, Serialize)]
+pub struct PaneRecord<'a> {
+    pub tab: &'a str,
+    pub pane_id: u32,
+    pub kind: &'static str,
+    pub title: &'a str,
+    /// Whether the pane is starred in any star group.
+    pub starred: bool,
+    /// The star groups the pane is starred in.
+    pub groups: Vec<&'a str>,
+    /// The one-based slot in the active star group.
+    pub slot: Option<usize>,
+    pub focused: bool,
+    pub previous_focus: bool,
+}
+
+/// Serialize records as JSON lines, one object per line.
+pub fn json_lines(records: &[PaneRecord]) -> String {
+    records
+        .iter()
+        .filter_map(|record| serde_json::to_string(record).ok())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_strings() {
+        let record = PaneRecord {
+            tab: "Tab \"1\"",
+            pane_id: 3,
+            kind: "plugin",
+            title: "a\tb",
+            starred: false,
+            groups: vec![],
+            slot: None,
+            focused: false,
+            previous_focus: false,
+        };
+
+        insta::assert_snapshot!(json_lines(&[record]));
+    }
+}
@@ -1,37 +1,161 @@
+mod columns;
+mod command;
+mod config;
+mod editor;
+mod focus;
+mod frame;
+mod frecency;
+mod global_star;
+mod history;
 mod keybind;
+mod listing;
+mod persist;
+mod preview;
+mod query;
 mod star;
+mod text;
+mod theme;
+use columns::Column;
 use nucleo_matcher::pattern::{CaseMatching, Normalization, Pattern};
-use nucleo_matcher::{Config, Matcher};
+use nucleo_matcher::{Config, Matcher, Utf32Str};
 use std::cmp::min;
+use std::cmp::Reverse;
 use std::collections::HashSet;
-use std::convert::TryFrom;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zellij_tile::prelude::*;
 
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Pane {
     tab_name: String,
+    tab_position: usize,
     pane_id: PaneId,
     pane_title: String,
+    command: Option<String>,
+    is_floating: bool,
+    exited: bool,
+    session: Option<Session>, // Only set for panes listed in the session mode.
 
     search_string: String,
 }
 
+/// The session of a pane listed in the session mode.
+#[derive(Debug, Clone, PartialEq)]
+struct Session {
+    name: String,
+    is_current: bool,
+    // Resurrectable sessions are not running, so Zellij does not report their panes.
+    // They are listed as a single row without a pane.
+    is_resurrectable: bool,
+}
+
 impl Pane {
     fn new(tab_name: String, pane_id: PaneId, pane_title: String) -> Self {
-        let just_pane_id = match pane_id {
-            PaneId::Terminal(id) => id,
-            PaneId::Plugin(id) => id,
-        };
-
-        let search_string = format!("{}  {} {}", tab_name, just_pane_id, pane_title);
-        Self {
+        let mut pane = Self {
             tab_name,
+            tab_position: 0,
             pane_id,
             pane_title,
-            search_string,
+            command: None,
+            is_floating: false,
+            exited: false,
+            session: None,
+            search_string: String::new(),
+        };
+        pane.update_search_string();
+        pane
+    }
+
+    fn with_command(mut self, command: Option<String>) -> Self {
+        self.command = command;
+        self.update_search_string();
+        self
+    }
+
+    fn with_floating(mut self, is_floating: bool) -> Self {
+        self.is_floating = is_floating;
+        self
+    }
+
+    fn with_exited(mut self, exited: bool) -> Self {
+        self.exited = exited;
+        self
+    }
+
+    fn with_tab_position(mut self, tab_position: usize) -> Self {
+        self.tab_position = tab_position;
+        self
+    }
+
+    fn with_session(mut self, session: Session) -> Self {
+        self.session = Some(session);
+        self.update_search_string();
+        self
+    }
+
+    /// Create the row of a resurrectable session.
+    fn resurrectable(name: String) -> Self {
+        Self::new(
+            String::new(),
+            PaneId::Terminal(0),
+            String::from("(resurrectable)"),
+        )
+        .with_session(Session {
+            name,
+            is_current: false,
+            is_resurrectable: true,
+        })
+    }
+
+    /// The pane ID is left out so that numbers in the query only match names.
+    /// Use the id: filter to search by pane ID.
+    fn update_search_string(&mut self) {
+        self.search_string = String::new();
+        if let Some(session) = &self.session {
+            self.search_string.push_str(&session.name);
+            self.search_string.push_str("  ");
+        }
+        self.search_string.push_str(&self.tab_name);
+        self.search_string.push_str("  ");
+        self.search_string.push_str(&self.pane_title);
+        if let Some(command) = &self.command {
+            self.search_string.push_str("  ");
+            self.search_string.push_str(command);
+        }
+    }
+
+    /// Return the char index in search_string where the tab name starts.
+    fn tab_start(&self) -> usize {
+        self.session
+            .as_ref()
+            .map_or(0, |session| session.name.chars().count() + 2)
+    }
+
+    /// Return the char index in search_string where the title starts.
+    fn title_start(&self) -> usize {
+        self.tab_start() + self.tab_name.chars().count() + 2
+    }
+
+    /// Return the char index in search_string where the command starts.
+    fn command_start(&self) -> usize {
+        self.title_start() + self.pane_title.chars().count() + 2
+    }
+
+    fn id(&self) -> u32 {
+        match self.pane_id {
+            PaneId::Terminal(id) => id,
+            PaneId::Plugin(id) => id,
+        }
+    }
+
+    fn star_key(&self) -> star::PaneKey {
+        star::PaneKey {
+            is_plugin: matches!(self.pane_id, PaneId::Plugin(_)),
+            tab_name: self.tab_name.clone(),
+            title: self.pane_title.clone(),
         }
     }
 }
@@ -42,23 +166,120 @@ impl AsRef<str> for Pane {
     }
 }
 
+/// A tab as listed in the tab mode of the picker.
+#[derive(Debug, Clone)]
+struct TabEntry {
+    position: usize,
+    name: String,
+    active: bool,
+    pane_ids: Vec<PaneId>,
+}
+
+impl AsRef<str> for TabEntry {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+/// What the picker lists.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum Mode {
+    #[default]
+    Panes,
+    Tabs,
+    Sessions,
+}
+
+impl Mode {
+    /// Return the mode that the switch mode key changes to.
+    fn next(self) -> Self {
+        match self {
+            Mode::Panes => Mode::Tabs,
+            Mode::Tabs => Mode::Sessions,
+            Mode::Sessions => Mode::Panes,
+        }
+    }
+}
+
+/// A row of the tree layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Row {
+    /// The header of the tab at the position.
+    Tab(usize),
+    /// The pane at the index in display_panes.
+    Pane(usize),
+}
+
+/// What to do with the text typed into the input prompt.
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    RenamePanes(Vec<PaneId>),
+    MoveToTab(Vec<PaneId>),
+    RenameTab(usize),
+}
+
+/// A prompt that temporarily replaces the search prompt to read the argument of an action.
+struct Input {
+    action: Action,
+    editor: editor::LineEditor,
+}
+
+impl Input {
+    fn new(action: Action, text: &str) -> Self {
+        Self {
+            action,
+            editor: editor::LineEditor::from(text),
+        }
+    }
+
+    fn prompt(&self) -> &'static str {
+        match self.action {
+            Action::RenamePanes(_) => "[RENAME] ",
+            Action::MoveToTab(_) => "[MOVE TO TAB] ",
+            Action::RenameTab(_) => "[RENAME TAB] ",
+        }
+    }
+}
+
 #[derive(Default)]
 struct State {
     tab_infos: Vec<TabInfo>,
     pane_infos: HashMap<usize, Vec<PaneInfo>>,
 
     panes: Vec<Pane>,
-
-    current_focus: Option<PaneId>,
-    previous_focus: Option<PaneId>,
-    search_key: String,
+    tabs: Vec<TabEntry>,
+    session_panes: Vec<Pane>,
+    mode: Mode,
+
+    focus_history: focus::FocusHistory,
+    frecency: frecency::Frecency,
+    search_key: editor::LineEditor,
+    input: Option<Input>,
+    history: history::SearchHistory,
+    history_path: Option<PathBuf>,
     display_panes: Vec<Pane>,
-    selected: usize, // selected always operates on display_panes.
-
-    stars: star::Star,
+    display_tabs: Vec<TabEntry>,
+    display_rows: Vec<Row>,    // Only built in the tree layout.
+    selected: usize,           // selected operates on display_panes, display_tabs, or display_rows.
+    collapsed: HashSet<usize>, // Positions of the tabs collapsed in the tree layout.
+    marked: HashSet<PaneId>,
+    show_preview: bool,
+    scroll_offset: usize, // Index of the first display pane in the viewport.
+    list_rows: usize,     // Number of display panes that fit into the viewport.
+
+    stars: star::StarGroups,
+    stars_path: Option<PathBuf>,
+    saved_stars: String,
+    global_stars: global_star::GlobalStars,
+    global_stars_path: Option<PathBuf>, // Only set when the global stars are enabled.
+    session_name: Option<String>,
 
     bound_key: bool,
     keybinds: keybind::Keybinds,
+    config: config::Config,
+    // Shown above the list: invalid options, for which the defaults are used meanwhile,
+    // or a failure to read or write the data folder.
+    error: Option<String>,
 
     plugin_id: Option<u32>,
 }
@@ -70,374 +291,3313 @@ impl State {
     /// the ordering of the events is unditerministic.
     fn update_state(&mut self) {
         let mut panes: Vec<Pane> = Vec::new();
+        let mut tabs = Vec::new();
         let mut current_focus = None;
 
         for (tab_id, tab_info) in self.tab_infos.iter().enumerate() {
+            let first_pane = panes.len();
             if let Some(pane_infos) = self.pane_infos.get(&tab_id) {
                 pane_infos.iter().for_each(|pane_info| {
-                    if pane_info.is_plugin && Some(pane_info.id) == self.plugin_id {
-                        return;
-                    }
-
-                    if pane_info.is_suppressed || !pane_info.is_selectable {
+                    let Some(pane) = list_pane(tab_info, pane_info, self.plugin_id) else {
                         return;
-                    }
-
-                    let pane_id = if pane_info.is_plugin {
-                        PaneId::Plugin(pane_info.id)
-                    } else {
-                        PaneId::Terminal(pane_info.id)
                     };
 
-                    panes.push(Pane::new(
-                        tab_info.name.clone(),
-                        pane_id,
-                        pane_info.title.clone(),
-                    ));
-
                     if pane_info.is_focused && tab_info.active && !pane_info.is_plugin {
-                        current_focus = Some(pane_id)
+                        current_focus = Some(pane.pane_id)
                     }
+                    panes.push(pane);
                 });
             }
+
+            tabs.push(TabEntry {
+                position: tab_info.position,
+                name: tab_info.name.clone(),
+                active: tab_info.active,
+                pane_ids: panes[first_pane..].iter().map(|p| p.pane_id).collect(),
+            });
         }
+        self.collapsed
+            .retain(|position| tabs.iter().any(|tab| tab.position == *position));
+        self.tabs = tabs;
 
         // Convert panes to hashset of paneid
         let pane_ids: HashSet<PaneId> = panes.iter().map(|p| p.pane_id).collect();
 
-        if current_focus.is_some() && current_focus != self.current_focus {
-            if self
-                .current_focus
-                .as_ref()
-                .is_some_and(|c| pane_ids.contains(c))
-            {
-                // If the previous current_focus still exists, use that as the
-                // next previous_focus.
-                self.previous_focus = self.current_focus;
-            } else if self
-                .previous_focus
+        if let Some(pane_id) = current_focus {
+            self.focus_history.visit(pane_id, &pane_ids);
+            self.frecency.visit(pane_id);
+        }
+        self.frecency.sync(&pane_ids);
+
+        self.marked.retain(|id| pane_ids.contains(id));
+        self.stars.sync(&pane_ids);
+        let candidates: Vec<(PaneId, star::PaneKey)> =
+            panes.iter().map(|p| (p.pane_id, p.star_key())).collect();
+        self.stars.restore(&candidates, now_millis());
+        self.panes = panes;
+        self.save_stars();
+        self.update_global_stars();
+    }
+
+    /// Point the stars file at the current session once its name is known.
+    /// The stars are loaded the first time, and saved under the new name on a rename.
+    fn update_stars_path(&mut self) {
+        let Some(session_name) = self.session_name.as_deref() else {
+            return;
+        };
+        let path = persist::session_file(session_name, "stars");
+        if self.stars_path.as_ref() == Some(&path) {
+            return;
+        }
+
+        let renamed = self.stars_path.is_some();
+        self.stars_path = Some(path);
+        if renamed {
+            self.saved_stars.clear();
+        } else {
+            self.load_stars();
+            let candidates: Vec<(PaneId, star::PaneKey)> = self
+                .panes
+                .iter()
+                .map(|p| (p.pane_id, p.star_key()))
+                .collect();
+            self.stars.restore(&candidates, now_millis());
+        }
+        self.save_stars();
+    }
+
+    /// Read the starred panes persisted by a previous instance of the plugin.
+    /// They are matched against the panes in the next update_state.
+    fn load_stars(&mut self) {
+        let Some(path) = self.stars_path.as_deref() else {
+            return;
+        };
+        match persist::read(path) {
+            Ok(Some(contents)) => {
+                // Stars of the v1 format go into the group that is active at startup.
+                let entries = star::decode(&contents, self.stars.first_name());
+                self.stars.set_pending(entries, now_millis());
+                self.saved_stars = contents;
+            }
+            Ok(None) => {}
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Persist the starred panes if they changed since the last write.
+    fn save_stars(&mut self) {
+        let Some(path) = self.stars_path.as_deref() else {
+            return;
+        };
+
+        let contents = star::encode(&self.stars.entries(|id| {
+            self.panes
+                .iter()
+                .find(|p| p.pane_id == *id)
+                .map(Pane::star_key)
+        }));
+
+        if contents == self.saved_stars {
+            return;
+        }
+
+        match persist::write(path, &contents) {
+            Ok(()) => self.saved_stars = contents,
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Merge the global stars that other sessions wrote into the global list.
+    fn load_global_stars(&mut self) {
+        let Some(path) = self.global_stars_path.as_deref() else {
+            return;
+        };
+        match persist::read(path) {
+            Ok(Some(contents)) => self
+                .global_stars
+                .merge(global_star::GlobalStars::decode(&contents), now_millis()),
+            Ok(None) => {}
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Persist the global list, keeping the changes that other sessions wrote meanwhile.
+    fn save_global_stars(&mut self) {
+        self.load_global_stars();
+
+        let Some(path) = self.global_stars_path.as_deref() else {
+            return;
+        };
+        if let Err(err) = persist::write(path, &self.global_stars.encode()) {
+            self.error = Some(err.to_string());
+        }
+    }
+
+    /// Return the entry of pane_id in the global list.
+    fn global_star_of(&self, pane_id: &PaneId) -> Option<global_star::GlobalStar> {
+        let pane = self.panes.iter().find(|pane| pane.pane_id == *pane_id)?;
+        Some(global_star::GlobalStar {
+            session: self.session_name.clone()?,
+            key: pane.star_key(),
+        })
+    }
+
+    /// Make the global list follow the stars of this session: the panes starred in any
+    /// group are starred, and the other panes are unstarred, including closed and
+    /// renamed ones. Stars that still wait to be restored are left as they are.
+    fn update_global_stars(&mut self) {
+        if self.global_stars_path.is_none() {
+            return;
+        }
+        let Some(session) = self.session_name.clone() else {
+            return;
+        };
+
+        let starred: Vec<star::PaneKey> = self
+            .panes
+            .iter()
+            .filter(|pane| !self.stars.groups_of(&pane.pane_id).is_empty())
+            .map(Pane::star_key)
+            .collect();
+        let stars = &self.stars;
+        let changed = self.global_stars.sync_session(
+            &session,
+            &starred,
+            |key| stars.is_pending(key),
+            now_millis(),
+        );
+        if changed {
+            self.save_global_stars();
+        }
+    }
+
+    /// Return the pane to switch to for star: a pane of this session,
+    /// a pane of another running session, or the row of a resurrectable session.
+    fn global_star_pane(&self, star: &global_star::GlobalStar) -> Option<&Pane> {
+        if self.session_name.as_ref() == Some(&star.session) {
+            return self.panes.iter().find(|pane| pane.star_key() == star.key);
+        }
+
+        self.session_panes.iter().find(|pane| {
+            pane.session.as_ref().is_some_and(|session| {
+                session.name == star.session
+                    && (session.is_resurrectable || pane.star_key() == star.key)
+            })
+        })
+    }
+
+    /// Return the pane of the global star after the focused pane,
+    /// or before it if forward is false. Stars that cannot be switched to are skipped.
+    fn next_global_star(&self, forward: bool) -> Option<&Pane> {
+        let current = self
+            .focus_history
+            .current()
+            .and_then(|pane_id| self.global_star_of(&pane_id));
+        let reachable = |star: &global_star::GlobalStar| self.global_star_pane(star).is_some();
+
+        let star = if forward {
+            self.global_stars.next(current.as_ref(), reachable)
+        } else {
+            self.global_stars.previous(current.as_ref(), reachable)
+        };
+        self.global_star_pane(star?)
+    }
+
+    /// Star or unstar pane_id in the active group.
+    fn toggle_star(&mut self, pane_id: PaneId) {
+        self.stars.active_mut().toggle(pane_id);
+        self.save_stars();
+        self.update_global_stars();
+    }
+
+    /// Star the pane in the active group if starred is true, unstar it if false,
+    /// or toggle it if None, and return the resulting `starred` or `unstarred` line.
+    fn star_pane(
+        &mut self,
+        pane_id: PaneId,
+        starred: Option<bool>,
+    ) -> Result<String, command::CommandError> {
+        if !self.pane_ids().contains(&pane_id) {
+            return Err(command::CommandError::NoSuchPane(command::format_pane_id(
+                &pane_id,
+            )));
+        }
+        if starred != Some(self.stars.active().has(&pane_id)) {
+            self.toggle_star(pane_id);
+        }
+        let state = if self.stars.active().has(&pane_id) {
+            "starred"
+        } else {
+            "unstarred"
+        };
+        Ok(format!("{}\n", state))
+    }
+
+    /// Focus the starred pane after the focused one, or before it if forward is false.
+    /// With the global stars, this cycles through the starred panes of all sessions.
+    fn focus_next_star(&mut self, forward: bool) {
+        if self.config.global_stars {
+            self.load_global_stars();
+            if let Some(pane) = self.next_global_star(forward) {
+                switch_to_pane(pane);
+            }
+            return;
+        }
+
+        let Some(pane_id) = self.focus_history.current() else {
+            return;
+        };
+        let star = self.stars.active();
+        let next = if forward {
+            star.next(&pane_id)
+        } else {
+            star.previous(&pane_id)
+        };
+        if let Some(id) = next {
+            focus_pane_with_id(*id, true);
+        }
+    }
+
+    /// Return the starred panes of the active group, one per line
+    /// with the slot, the pane ID, the tab name, and the title separated by tabs.
+    fn list_stars(&self) -> String {
+        (0..)
+            .map_while(|slot| self.stars.active().get(slot))
+            .enumerate()
+            .filter_map(|(slot, pane_id)| {
+                let pane = self.panes.iter().find(|pane| pane.pane_id == *pane_id)?;
+                Some(format!(
+                    "{}\t{}\t{}\t{}\n",
+                    slot + 1,
+                    command::format_pane_id(pane_id),
+                    pane.tab_name,
+                    pane.pane_title
+                ))
+            })
+            .collect()
+    }
+
+    /// Return all panes of this session in tab order as JSON lines.
+    fn list_panes(&self) -> String {
+        let current = self.focus_history.current();
+        let previous = self.focus_history.previous();
+
+        let records: Vec<listing::PaneRecord> = self
+            .panes
+            .iter()
+            .map(|pane| {
+                let slot = self.stars.active().slot_of(&pane.pane_id);
+                let groups = self.stars.groups_of(&pane.pane_id);
+                listing::PaneRecord {
+                    tab: &pane.tab_name,
+                    pane_id: pane.id(),
+                    kind: match pane.pane_id {
+                        PaneId::Terminal(_) => star::KIND_TERMINAL,
+                        PaneId::Plugin(_) => star::KIND_PLUGIN,
+                    },
+                    title: &pane.pane_title,
+                    starred: !groups.is_empty(),
+                    groups,
+                    slot: slot.map(|slot| slot + 1),
+                    focused: current == Some(pane.pane_id),
+                    previous_focus: previous == Some(pane.pane_id),
+                }
+            })
+            .collect();
+        listing::json_lines(&records)
+    }
+
+    /// Run a command received through the CLI pipe and return its output.
+    fn run_command(&mut self, command: command::Command) -> Result<String, command::CommandError> {
+        match command {
+            command::Command::Focus(query) => {
+                let pane = self
+                    .search(&self.panes, &query)
+                    .into_iter()
+                    .next()
+                    .ok_or(command::CommandError::NoMatch(query))?;
+                focus_pane_with_id(pane.pane_id, true);
+                Ok(format!("{}\n", command::format_pane_id(&pane.pane_id)))
+            }
+            command::Command::Star(pane_id) => self.star_pane(pane_id, Some(true)),
+            command::Command::Unstar(pane_id) => self.star_pane(pane_id, Some(false)),
+            command::Command::ToggleStar(pane_id) => self.star_pane(pane_id, None),
+            command::Command::ListStars => Ok(self.list_stars()),
+            command::Command::ListPanes => Ok(self.list_panes()),
+            command::Command::NextStar => {
+                self.focus_next_star(true);
+                Ok(String::new())
+            }
+            command::Command::PreviousStar => {
+                self.focus_next_star(false);
+                Ok(String::new())
+            }
+            command::Command::Back => {
+                if let Some(id) = self.focus_history.previous() {
+                    focus_pane_with_id(id, true);
+                }
+                Ok(String::new())
+            }
+            command::Command::Open(query) => {
+                self.open_picker(&query);
+                Ok(String::new())
+            }
+            command::Command::Jump(query) => match self.jump(&query) {
+                Some(pane_id) => Ok(format!("{}\n", command::format_pane_id(&pane_id))),
+                None => Ok(String::from("ambiguous\n")),
+            },
+        }
+    }
+
+    /// Read the search history persisted by a previous instance of the plugin.
+    fn load_history(&mut self) {
+        let Some(path) = self.history_path.as_deref() else {
+            return;
+        };
+        match persist::read(path) {
+            Ok(Some(contents)) => self.history.decode(&contents),
+            Ok(None) => {}
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Record the search key in the search history and persist it.
+    fn submit_search(&mut self) {
+        self.history.push(self.search_key.text());
+        self.search_key.clear();
+
+        if let Some(path) = self.history_path.as_deref() {
+            if let Err(err) = persist::write(path, &self.history.encode()) {
+                self.error = Some(err.to_string());
+            }
+        }
+    }
+
+    /// Build the table of panes that match the search key,
+    /// with as many rows as fit into height including the header.
+    /// The columns and their widths follow the columns option.
+    /// The session mode lists the panes of all sessions.
+    fn panes_as_table(&mut self, width: usize, height: usize) -> Table {
+        // Pane IDs of other sessions can be equal to those of this session,
+        // so stars, marks, and the focus history are left out in the session mode.
+        let sessions = self.mode == Mode::Sessions;
+        let panes = if sessions {
+            &self.session_panes
+        } else {
+            &self.panes
+        };
+
+        // The mark column is only shown while panes are marked.
+        let mark_col_space = if sessions || self.marked.is_empty() {
+            0
+        } else {
+            2
+        };
+        let (columns, widths) = self.pane_columns(panes, width.saturating_sub(mark_col_space), "");
+
+        self.display_panes = self.search(panes, self.search_key.text());
+        self.update_viewport(height);
+
+        let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+        let query = query::Query::parse(self.search_key.text());
+        let pattern = Pattern::parse(query.text(), CaseMatching::Ignore, Normalization::Smart);
+
+        let mut header = Vec::new();
+        if mark_col_space > 0 {
+            header.push(String::from(" "));
+        }
+        header.extend(column_headers(&columns, &widths));
+
+        let mut table = Table::new().add_styled_row(self.header_row(header));
+
+        let viewport = self
+            .display_panes
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(self.list_rows);
+        for (i, pane) in viewport {
+            let mut row = Vec::new();
+            if mark_col_space > 0 {
+                row.push(self.mark_cell(pane));
+            }
+            row.extend(self.pane_cells(pane, &columns, &widths, "", &pattern, &mut matcher));
+
+            if i == self.selected {
+                row = row.into_iter().map(Text::selected).collect();
+            }
+            table = table.add_styled_row(row);
+        }
+
+        table
+    }
+
+    /// Return the columns of the pane list that fit into width, and their widths.
+    /// The titles are indented by indent, e.g., under the tab headers of the tree layout.
+    fn pane_columns(
+        &self,
+        panes: &[Pane],
+        width: usize,
+        indent: &str,
+    ) -> (Vec<columns::ColumnSpec>, Vec<usize>) {
+        let sessions = self.mode == Mode::Sessions;
+        let columns: Vec<columns::ColumnSpec> = self
+            .config
+            .columns
+            .iter()
+            .copied()
+            .filter(|spec| match spec.column {
+                Column::Session => sessions,
+                // The tree layout shows the tab names in the tab headers.
+                Column::Tab => !self.tree_layout(),
+                // The groups column is only shown when there is more than one group to tell apart.
+                Column::Groups => !sessions && self.stars.group_count() > 1,
+                Column::Command => panes.iter().any(|pane| pane.command.is_some()),
+                _ => true,
+            })
+            .collect();
+        let wants: Vec<(columns::ColumnSpec, usize)> = columns
+            .into_iter()
+            .map(|spec| {
+                let indent = match spec.column {
+                    Column::Title => text::width(indent),
+                    _ => 0,
+                };
+                let want = panes
+                    .iter()
+                    .map(|pane| indent + text::width(&self.cell(spec.column, pane)))
+                    .max()
+                    .unwrap_or(0);
+                (spec, want.max(text::width(spec.column.header())))
+            })
+            .collect();
+        // Columns of low priority are hidden when the rest does not fit without shrinking.
+        let (columns, wants): (Vec<columns::ColumnSpec>, Vec<(columns::Width, usize)>) =
+            columns::fit(&wants, width)
+                .into_iter()
+                .map(|(spec, want)| (spec, (spec.width, want)))
+                .unzip();
+        let widths = columns::layout(&wants, width);
+        (columns, widths)
+    }
+
+    /// Return the cells of pane in columns clipped to widths,
+    /// with the chars that match pattern highlighted. The title is indented by indent.
+    fn pane_cells(
+        &self,
+        pane: &Pane,
+        columns: &[columns::ColumnSpec],
+        widths: &[usize],
+        indent: &str,
+        pattern: &Pattern,
+        matcher: &mut Matcher,
+    ) -> Vec<Text> {
+        // Split the matched characters of search_string into the columns they belong to.
+        let indices = match_indices(pattern, matcher, &pane.search_string);
+        let tab_start = pane.tab_start();
+        let title_start = pane.title_start();
+        let command_start = pane.command_start();
+        let is_resurrectable = pane.session.as_ref().is_some_and(|s| s.is_resurrectable);
+        let theme = self.config.theme;
+
+        let mut cells = Vec::new();
+        for (spec, &width) in columns.iter().zip(widths) {
+            let mut content = self.cell(spec.column, pane);
+            let matched: Vec<usize> = match spec.column {
+                Column::Session => indices
+                    .iter()
+                    .filter(|i| **i < tab_start)
+                    .copied()
+                    .collect(),
+                Column::Tab => indices
+                    .iter()
+                    .filter(|i| (tab_start..title_start).contains(*i))
+                    .map(|i| i - tab_start)
+                    .collect(),
+                Column::Title => {
+                    content = format!("{}{}", indent, content);
+                    indices
+                        .iter()
+                        .filter(|i| (title_start..command_start).contains(*i))
+                        .map(|i| i - title_start + indent.chars().count())
+                        .collect()
+                }
+                Column::Command => indices
+                    .iter()
+                    .filter(|i| **i >= command_start)
+                    .map(|i| i - command_start)
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            let ellipsis = match spec.column {
+                Column::Title | Column::Command => self.config.ellipsis,
+                _ => text::Ellipsis::End,
+            };
+            let clipped = text::clip(&content, width, ellipsis);
+            let text = Text::new(&clipped.text);
+            let text = match spec.column {
+                Column::Star | Column::Slot | Column::Groups => {
+                    theme::color_range(text, theme.star, ..)
+                }
+                Column::Session if is_resurrectable => theme::color_range(text, theme.dimmed, ..),
+                Column::Session => theme::color_range(text, theme.session, ..),
+                Column::Title => theme::color_range(text, self.title_color(pane), ..),
+                _ => text,
+            };
+            cells.push(color_matches(
+                text,
+                theme.matched,
+                &clipped,
+                matched.into_iter(),
+            ));
+        }
+        cells
+    }
+
+    /// Return the cell of the mark column for pane.
+    fn mark_cell(&self, pane: &Pane) -> Text {
+        let mark = if self.marked.contains(&pane.pane_id) {
+            ">"
+        } else {
+            " "
+        };
+        theme::color_range(Text::new(mark), self.config.theme.mark, ..)
+    }
+
+    /// Return the cells of the header row of a table.
+    fn header_row(&self, header: Vec<String>) -> Vec<Text> {
+        header
+            .into_iter()
+            .map(|title| theme::color_range(Text::new(title), self.config.theme.header, ..))
+            .collect()
+    }
+
+    /// Return the color of the title of pane: dimmed for exited panes and
+    /// resurrectable sessions, highlighted for the current and previous pane.
+    fn title_color(&self, pane: &Pane) -> Option<usize> {
+        let theme = &self.config.theme;
+        let is_resurrectable = pane.session.as_ref().is_some_and(|s| s.is_resurrectable);
+        if pane.exited || is_resurrectable {
+            theme.dimmed
+        } else if pane.session.is_some() {
+            // The focus history only knows the panes of this session.
+            None
+        } else if self.focus_history.current() == Some(pane.pane_id) {
+            theme.current_pane
+        } else if self.focus_history.previous() == Some(pane.pane_id) {
+            theme.previous_pane
+        } else {
+            None
+        }
+    }
+
+    /// Return the text of column for pane in the pane list.
+    fn cell(&self, column: Column, pane: &Pane) -> String {
+        // Panes of other sessions have no stars in this session.
+        let slot = match pane.session {
+            None => self.stars.active().slot_of(&pane.pane_id),
+            Some(_) => None,
+        };
+        let is_resurrectable = pane.session.as_ref().is_some_and(|s| s.is_resurrectable);
+
+        match column {
+            // Show the slot number for the slots that can be jumped to directly.
+            Column::Star => match slot {
+                Some(slot) if slot < keybind::FOCUS_SLOTS.len() => (slot + 1).to_string(),
+                Some(_) => String::from("*"),
+                None => String::from(" "),
+            },
+            Column::Slot => slot.map_or(String::new(), |slot| (slot + 1).to_string()),
+            Column::Session => pane
+                .session
                 .as_ref()
-                .is_some_and(|c| pane_ids.contains(c))
+                .map_or(String::new(), |s| s.name.clone()),
+            Column::Tab => pane.tab_name.clone(),
+            Column::Id if is_resurrectable => String::from("   "),
+            Column::Id => format!("{:3}", pane.id()),
+            Column::Kind if is_resurrectable => String::new(),
+            Column::Kind => match pane.pane_id {
+                PaneId::Terminal(_) => String::from("terminal"),
+                PaneId::Plugin(_) => String::from("plugin"),
+            },
+            Column::Groups => self.stars.groups_of(&pane.pane_id).join(","),
+            Column::Title => pane.pane_title.clone(),
+            Column::Command => pane.command.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Build the tree of tabs and their panes that match the search key,
+    /// with as many rows as fit into height including the header.
+    /// Tabs are ordered by their best matching pane, collapsed tabs list no panes.
+    /// The panes have the columns of the pane list, except for the tab.
+    fn tree_as_table(&mut self, width: usize, height: usize) -> Table {
+        let indent = "  ";
+
+        // Group the matches by tab, keeping their order inside each tab.
+        let mut groups: Vec<(usize, Vec<Pane>)> = Vec::new();
+        for pane in self.search(&self.panes, self.search_key.text()) {
+            match groups
+                .iter_mut()
+                .find(|(position, _)| *position == pane.tab_position)
             {
-                // Not replacing the old previous_focus because the old
-                // current_focus was not found in the current panes (could be already deleted)
-                // and the old previous_focus still exists.
-            } else {
-                self.previous_focus = None;
+                Some((_, panes)) => panes.push(pane),
+                None => groups.push((pane.tab_position, vec![pane])),
+            }
+        }
+
+        self.display_panes = Vec::new();
+        self.display_rows = Vec::new();
+        for (position, panes) in groups {
+            self.display_rows.push(Row::Tab(position));
+            for pane in panes {
+                if !self.collapsed.contains(&position) {
+                    self.display_rows.push(Row::Pane(self.display_panes.len()));
+                }
+                self.display_panes.push(pane);
+            }
+        }
+        self.update_viewport(height);
+
+        // The markers of the tab headers take the first column.
+        let mark_col_space = if self.marked.is_empty() { 0 } else { 2 };
+        let (columns, widths) = self.pane_columns(
+            &self.panes,
+            width.saturating_sub(2 + mark_col_space),
+            indent,
+        );
+        // The tab headers are written into the title column, or the widest one without it.
+        let label_column = columns
+            .iter()
+            .position(|spec| spec.column == Column::Title)
+            .or_else(|| (0..widths.len()).max_by_key(|&i| widths[i]));
+
+        let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+        let query = query::Query::parse(self.search_key.text());
+        let pattern = Pattern::parse(query.text(), CaseMatching::Ignore, Normalization::Smart);
+
+        let mut header = vec![String::from(" ")];
+        if mark_col_space > 0 {
+            header.push(String::from(" "));
+        }
+        header.extend(column_headers(&columns, &widths));
+
+        let mut table = Table::new().add_styled_row(self.header_row(header));
+
+        let viewport = self
+            .display_rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(self.list_rows);
+        for (i, row) in viewport {
+            let mut cells = match *row {
+                Row::Tab(position) => {
+                    let label = self.tab_label(position);
+                    let marker = self.tree_marker(position);
+                    let header = self.config.theme.header;
+                    let mut cells = vec![theme::color_range(Text::new(marker), header, ..)];
+                    if mark_col_space > 0 {
+                        cells.push(Text::new(" "));
+                    }
+                    for (j, &width) in widths.iter().enumerate() {
+                        cells.push(if Some(j) == label_column {
+                            let label = text::clip(&label, width, text::Ellipsis::End);
+                            theme::color_range(Text::new(label.text), header, ..)
+                        } else {
+                            Text::new(" ")
+                        });
+                    }
+                    cells
+                }
+                Row::Pane(index) => {
+                    let pane = &self.display_panes[index];
+                    let mut cells = vec![Text::new(" ")];
+                    if mark_col_space > 0 {
+                        cells.push(self.mark_cell(pane));
+                    }
+                    cells.extend(self.pane_cells(
+                        pane,
+                        &columns,
+                        &widths,
+                        indent,
+                        &pattern,
+                        &mut matcher,
+                    ));
+                    cells
+                }
+            };
+            if i == self.selected {
+                cells = cells.into_iter().map(Text::selected).collect();
             }
-            self.current_focus = current_focus;
+
+            table = table.add_styled_row(cells);
         }
 
-        self.stars.sync(&pane_ids);
-        self.panes = panes;
+        table
+    }
+
+    /// Return the panes that match search_key, the best match first.
+    fn search(&self, panes: &[Pane], search_key: &str) -> Vec<Pane> {
+        self.search_scored(panes, search_key)
+            .into_iter()
+            .map(|(pane, _)| pane.clone())
+            .collect()
+    }
+
+    /// Return the panes that match search_key with their match scores, the best match first.
+    fn search_scored<'a>(&self, panes: &'a [Pane], search_key: &str) -> Vec<(&'a Pane, u32)> {
+        let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+        let query = query::Query::parse(search_key);
+        let pattern = Pattern::parse(query.text(), CaseMatching::Ignore, Normalization::Smart);
+
+        // Panes of other sessions have no stars or focus history in this session.
+        let candidates = panes.iter().filter(|pane| {
+            query.matches(&query::Fields {
+                tab_name: &pane.tab_name,
+                title: &pane.pane_title,
+                id: pane.id(),
+                command: pane.command.as_deref(),
+                session: pane.session.as_ref().map(|s| s.name.as_str()),
+                is_plugin: matches!(pane.pane_id, PaneId::Plugin(_)),
+                is_floating: pane.is_floating,
+                is_starred: pane.session.is_none() && self.stars.active().has(&pane.pane_id),
+            })
+        });
+        let mut search_result = pattern.match_list(candidates, &mut matcher);
+
+        // The sort is stable, so panes that tie keep their tab order.
+        search_result.sort_by_key(|(pane, score)| {
+            let tiebreak = match pane.session {
+                None => self.tiebreak(&pane.pane_id),
+                Some(_) => 0,
+            };
+            (Reverse(*score), tiebreak)
+        });
+        search_result
+    }
+
+    /// Return the pane to jump to for query: the best match if it beats
+    /// the second best by at least the configured score gap, None otherwise.
+    fn jump_target(&self, query: &str) -> Option<PaneId> {
+        let matches = self.search_scored(&self.panes, query);
+        let (best, best_score) = matches.first()?;
+        let ambiguous = matches
+            .get(1)
+            .is_some_and(|(_, score)| best_score - score < self.config.jump_score_gap);
+        if ambiguous {
+            None
+        } else {
+            Some(best.pane_id)
+        }
+    }
+
+    /// Focus the pane that query picks, or open the picker with query
+    /// when no pane or more than one pane is a good match.
+    fn jump(&mut self, query: &str) -> Option<PaneId> {
+        match self.jump_target(query) {
+            Some(pane_id) => {
+                focus_pane_with_id(pane_id, true);
+                Some(pane_id)
+            }
+            None => {
+                self.open_picker(query);
+                None
+            }
+        }
+    }
+
+    /// Show the picker listing panes with query in the search prompt.
+    fn open_picker(&mut self, query: &str) {
+        self.switch_mode(Mode::Panes);
+        self.search_key = editor::LineEditor::from(query);
+        show_self(true);
+    }
+
+    /// Build the table of tabs that match the search key,
+    /// with as many rows as fit into height including the header.
+    fn tabs_as_table(&mut self, width: usize, height: usize) -> Table {
+        let mut matcher = Matcher::new(Config::DEFAULT);
+        let pattern = Pattern::parse(
+            self.search_key.text(),
+            CaseMatching::Ignore,
+            Normalization::Smart,
+        );
+        let mut search_result = pattern.match_list(self.tabs.iter(), &mut matcher);
+
+        // The sort is stable, so tabs that tie keep their order.
+        search_result.sort_by_key(|(_, score)| Reverse(*score));
+
+        self.display_tabs = search_result
+            .into_iter()
+            .map(|(tab, _)| tab.clone())
+            .collect();
+
+        self.update_viewport(height);
+
+        // The counts are hidden, the stars first, rather than clipping the tab names
+        // shorter than their header. The active marker and the names are always shown.
+        let name_header = "Tab";
+        let count_headers = ["Panes", "Stars"];
+        let counts_width = |n: usize| -> usize {
+            count_headers[..n]
+                .iter()
+                .map(|header| header.len() + 1)
+                .sum()
+        };
+        let counts = (0..=count_headers.len())
+            .rev()
+            .find(|&n| 2 + name_header.len() + counts_width(n) <= width)
+            .unwrap_or(0);
+        let name_width = width.saturating_sub(2 + counts_width(counts));
+
+        let mut header = vec![
+            String::from(" "),
+            text::pad(
+                &text::clip(name_header, name_width, text::Ellipsis::End).text,
+                name_width,
+            ),
+        ];
+        header.extend(
+            count_headers[..counts]
+                .iter()
+                .map(|header| header.to_string()),
+        );
+        let mut table = Table::new().add_styled_row(self.header_row(header));
+
+        let viewport = self
+            .display_tabs
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(self.list_rows);
+        for (i, tab) in viewport {
+            let starred = tab
+                .pane_ids
+                .iter()
+                .filter(|id| self.stars.active().has(id))
+                .count();
+
+            let indices = match_indices(&pattern, &mut matcher, &tab.name);
+            let name = text::clip(&tab.name, name_width, text::Ellipsis::End);
+            let mut row = vec![
+                theme::color_range(
+                    Text::new(if tab.active { ">" } else { " " }),
+                    self.config.theme.mark,
+                    ..,
+                ),
+                color_matches(
+                    Text::new(&name.text),
+                    self.config.theme.matched,
+                    &name,
+                    indices.into_iter(),
+                ),
+            ];
+            for (header, count) in count_headers[..counts]
+                .iter()
+                .zip([tab.pane_ids.len(), starred])
+            {
+                row.push(Text::new(format!(
+                    "{:>width$}",
+                    count,
+                    width = header.len()
+                )));
+            }
+            if i == self.selected {
+                row = row.into_iter().map(Text::selected).collect();
+            }
+
+            table = table.add_styled_row(row);
+        }
+
+        table
+    }
+
+    /// Return the key that orders panes with the same match score, smaller first.
+    /// Without a search query all panes have the same score.
+    fn tiebreak(&self, pane_id: &PaneId) -> usize {
+        match self.config.sort_by {
+            config::SortBy::Tab => 0,
+            config::SortBy::Mru => self.focus_history.rank(pane_id).unwrap_or(usize::MAX),
+            config::SortBy::Frecency => usize::MAX - self.frecency.score(pane_id) as usize,
+        }
+    }
+
+    /// Return the pane info that Zellij reported for pane_id.
+    fn pane_info(&self, pane_id: &PaneId) -> Option<&PaneInfo> {
+        self.pane_infos
+            .values()
+            .flatten()
+            .find(|info| match pane_id {
+                PaneId::Terminal(id) => !info.is_plugin && info.id == *id,
+                PaneId::Plugin(id) => info.is_plugin && info.id == *id,
+            })
+    }
+
+    /// Draw the details of the selected pane into rect.
+    fn render_preview(&self, rect: preview::Rect) {
+        let Some(pane) = self.selected_pane() else {
+            return;
+        };
+        let Some(pane_info) = self.pane_info(&pane.pane_id) else {
+            return;
+        };
+
+        let lines = preview::lines(&pane.tab_name, pane_info);
+        for (i, line) in lines.iter().take(rect.rows).enumerate() {
+            let mut text = Text::new(text::clip(line, rect.cols, text::Ellipsis::End).text);
+            if i == 0 {
+                text = theme::color_range(text, self.config.theme.header, ..);
+            }
+            print_text_with_coordinates(text, rect.x, rect.y + i, Some(rect.cols), Some(1));
+        }
+    }
+
+    /// Build the table of the mode, which computes the panes or tabs that match,
+    /// and return it with the counter of matches.
+    fn build_table(&mut self, width: usize, height: usize) -> (Table, String) {
+        match self.mode {
+            Mode::Panes if self.tree_layout() => (
+                self.tree_as_table(width, height),
+                format!("{}/{}", self.display_panes.len(), self.panes.len()),
+            ),
+            Mode::Panes => (
+                self.panes_as_table(width, height),
+                format!("{}/{}", self.display_panes.len(), self.panes.len()),
+            ),
+            Mode::Tabs => (
+                self.tabs_as_table(width, height),
+                format!("{}/{}", self.display_tabs.len(), self.tabs.len()),
+            ),
+            Mode::Sessions => (
+                self.panes_as_table(width, height),
+                format!("{}/{}", self.display_panes.len(), self.session_panes.len()),
+            ),
+        }
+    }
+
+    /// Draw the prompt with the cursor into rect,
+    /// and the counter right-aligned when it fits.
+    fn render_prompt(&self, rect: preview::Rect, counter: &str) {
+        let (prompt, line) = match (&self.input, self.mode) {
+            (Some(input), _) => (input.prompt(), &input.editor),
+            (None, Mode::Panes) => ("[SEARCH] ", &self.search_key),
+            (None, Mode::Tabs) => ("[TABS] ", &self.search_key),
+            (None, Mode::Sessions) => ("[SESSIONS] ", &self.search_key),
+        };
+        let theme = &self.config.theme;
+        let prompt_line = format!("{}{}", prompt, line.text());
+        let text = Text::new(text::clip(&prompt_line, rect.cols, text::Ellipsis::End).text);
+        let text = theme::color_range(text, theme.prompt, 0..prompt.len() - 1);
+        print_text_with_coordinates(
+            theme::color_range(text, theme.query, prompt.len()..),
+            rect.x,
+            rect.y,
+            Some(rect.cols),
+            Some(1),
+        );
+
+        // Draw the cursor over the char under it, or a space at the end of the line.
+        // Wide chars before the cursor take two cells.
+        let cursor = line.cursor();
+        let before_cursor: String = line.text().chars().take(cursor).collect();
+        let under_cursor = line.text().chars().nth(cursor).unwrap_or(' ');
+        let cursor_x = rect.x + text::width(prompt) + text::width(&before_cursor);
+        if cursor_x < rect.x + rect.cols {
+            print_text_with_coordinates(
+                Text::new(under_cursor.to_string()).selected(),
+                cursor_x,
+                rect.y,
+                None,
+                None,
+            );
+        }
+
+        // The number of matches and the active group are right-aligned.
+        let mut status = Text::new(counter);
+        let mut status_len = counter.len();
+        if self.stars.group_count() > 1 {
+            let group = format!(" [{}]", self.stars.active_name());
+            status_len += text::width(&group);
+            status = theme::color_range(
+                Text::new(format!("{}{}", counter, group)),
+                theme.star,
+                counter.len()..,
+            );
+        }
+        if text::width(prompt) + status_len < rect.cols {
+            let x = rect.x + rect.cols - status_len - 1;
+            print_text_with_coordinates(status, x, rect.y, None, None);
+        }
+    }
+
+    /// Draw the prompt, a configuration error, the table, and the preview.
+    fn render_table(&mut self, prompt: preview::Rect, layout: preview::Layout) {
+        // The table is built first because it computes the panes or tabs that match.
+        // It leaves the last column free as a margin, like the column left of the list.
+        let (table, counter) =
+            self.build_table(layout.list.cols.saturating_sub(1), layout.list.rows);
+        self.render_prompt(prompt, &counter);
+
+        if let Some(error) = &self.error {
+            print_text_with_coordinates(
+                theme::color_range(
+                    Text::new(text::clip(error, prompt.cols, text::Ellipsis::End).text),
+                    self.config.theme.error,
+                    ..,
+                ),
+                prompt.x,
+                prompt.y + 1,
+                Some(prompt.cols),
+                Some(1),
+            );
+        }
+
+        print_table_with_coordinates(
+            table,
+            layout.list.x,
+            layout.list.y,
+            Some(layout.list.cols),
+            Some(layout.list.rows),
+        );
+
+        if let Some(rect) = layout.preview {
+            if self.mode == Mode::Panes {
+                self.render_preview(rect);
+            }
+        }
+    }
+
+    /// Draw the prompt and one line per listed item into list, without a header.
+    fn render_compact(&mut self, prompt: preview::Rect, list: preview::Rect) {
+        // The tables save a row for their header, which the compact list does not have.
+        let (_, counter) = self.build_table(list.cols, list.rows + 1);
+        self.render_prompt(prompt, &counter);
+
+        for (i, line) in self.compact_lines().iter().enumerate() {
+            let mut text = Text::new(text::clip(line, list.cols, text::Ellipsis::End).text);
+            if self.scroll_offset + i == self.selected {
+                text = text.selected();
+            }
+            print_text_with_coordinates(text, list.x, list.y + i, Some(list.cols), Some(1));
+        }
+    }
+
+    /// Return the lines of the compact list in the viewport.
+    /// Panes start with their title since there are no columns to line up.
+    fn compact_lines(&self) -> Vec<String> {
+        let end = min(self.display_len(), self.scroll_offset + self.list_rows);
+        (self.scroll_offset..end)
+            .map(|i| match self.mode {
+                Mode::Tabs => self.display_tabs[i].name.clone(),
+                Mode::Panes if self.tree_layout() => match self.display_rows[i] {
+                    Row::Tab(position) => {
+                        format!(
+                            "{} {}",
+                            self.tree_marker(position),
+                            self.tab_label(position)
+                        )
+                    }
+                    Row::Pane(index) => format!("  {}", self.display_panes[index].pane_title),
+                },
+                Mode::Panes | Mode::Sessions => {
+                    let pane = &self.display_panes[i];
+                    let place = match &pane.session {
+                        Some(session) if session.is_resurrectable => session.name.clone(),
+                        Some(session) => format!("{}/{}", session.name, pane.tab_name),
+                        None => pane.tab_name.clone(),
+                    };
+                    let star = self.cell(Column::Star, pane);
+                    format!("{} {} ({})", star, pane.pane_title, place)
+                }
+            })
+            .collect()
+    }
+
+    /// Return the marker of the tab header at position in the tree layout.
+    fn tree_marker(&self, position: usize) -> &'static str {
+        if self.collapsed.contains(&position) {
+            "▸"
+        } else {
+            "▾"
+        }
+    }
+
+    /// Return the text of the tab header at position in the tree layout:
+    /// the position, the name, and the number of listed panes of the tab.
+    fn tab_label(&self, position: usize) -> String {
+        let panes: Vec<&Pane> = self
+            .display_panes
+            .iter()
+            .filter(|pane| pane.tab_position == position)
+            .collect();
+        let name = panes.first().map_or("", |pane| pane.tab_name.as_str());
+        format!("{} {} ({})", position + 1, name, panes.len())
+    }
+
+    /// Return the panes that actions apply to:
+    /// the marked panes in list order, or the selected pane when none are marked.
+    fn targets(&self) -> Vec<PaneId> {
+        if self.marked.is_empty() {
+            return self
+                .selected_pane()
+                .map(|pane| vec![pane.pane_id])
+                .unwrap_or_default();
+        }
+
+        self.panes
+            .iter()
+            .map(|pane| pane.pane_id)
+            .filter(|id| self.marked.contains(id))
+            .collect()
+    }
+
+    /// Mark or unmark the selected pane and move the selection down.
+    fn toggle_mark(&mut self) {
+        if let Some(pane_id) = self.selected_pane().map(|pane| pane.pane_id) {
+            if !self.marked.remove(&pane_id) {
+                self.marked.insert(pane_id);
+            }
+            self.select_downward();
+        }
+    }
+
+    /// Star all targets, or unstar them if they are all starred already.
+    fn toggle_star_targets(&mut self) {
+        let targets = self.targets();
+        let star = self.stars.active_mut();
+        let all_starred = targets.iter().all(|id| star.has(id));
+        for id in &targets {
+            if star.has(id) == all_starred {
+                star.toggle(*id);
+            }
+        }
+        self.save_stars();
+        self.update_global_stars();
+    }
+
+    /// Open the input prompt to rename the targets.
+    /// A single pane starts with its current title to edit.
+    fn start_rename(&mut self) {
+        let targets = self.targets();
+        let title = match targets.as_slice() {
+            [] => return,
+            [pane_id] => self
+                .panes
+                .iter()
+                .find(|pane| pane.pane_id == *pane_id)
+                .map_or("", |pane| pane.pane_title.as_str()),
+            _ => "",
+        };
+        self.input = Some(Input::new(Action::RenamePanes(targets.clone()), title));
+    }
+
+    /// Run the action of the input prompt with the typed text.
+    fn submit_input(&mut self, input: Input) {
+        let text = input.editor.text().trim();
+        match input.action {
+            Action::RenamePanes(pane_ids) => {
+                if text.is_empty() {
+                    return;
+                }
+                for pane_id in pane_ids {
+                    rename_pane_with_id(pane_id, text);
+                }
+            }
+            Action::MoveToTab(pane_ids) => {
+                if text.is_empty() {
+                    return;
+                }
+                match resolve_tab(&self.tab_infos, text) {
+                    Some(index) => break_panes_to_tab_with_index(&pane_ids, index, true),
+                    None => break_panes_to_new_tab(&pane_ids, Some(text.to_string()), true),
+                }
+            }
+            Action::RenameTab(position) => {
+                if !text.is_empty() {
+                    rename_tab(tab_index(position), text);
+                }
+                return;
+            }
+        }
+        self.marked.clear();
+    }
+
+    fn handle_pane_key(&mut self, key: KeyWithModifier) {
+        if Some(key.clone()) == self.keybinds.plugin_navigate_to {
+            if let Some(Row::Tab(position)) = self.selected_row() {
+                if !self.collapsed.remove(&position) {
+                    self.collapsed.insert(position);
+                }
+            } else if let Some(pane) = self.selected_pane() {
+                focus_pane_with_id(pane.pane_id, true);
+                self.submit_search();
+                hide_self();
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_hide {
+            self.search_key.clear();
+            self.marked.clear();
+            self.history.reset();
+            hide_self();
+        } else if Some(key.clone()) == self.keybinds.plugin_history_previous {
+            if let Some(query) = self.history.previous(self.search_key.text()) {
+                self.search_key = editor::LineEditor::from(query);
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_history_next {
+            if let Some(query) = self.history.next() {
+                self.search_key = editor::LineEditor::from(query);
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_toggle_star {
+            self.toggle_star_targets();
+        } else if Some(key.clone()) == self.keybinds.plugin_toggle_mark {
+            self.toggle_mark();
+        } else if Some(key.clone()) == self.keybinds.plugin_close_panes {
+            for pane_id in self.targets() {
+                close_pane_with_id(pane_id);
+            }
+            self.marked.clear();
+        } else if Some(key.clone()) == self.keybinds.plugin_toggle_floating {
+            for pane_id in self.targets() {
+                toggle_pane_embed_or_eject_for_pane_id(pane_id);
+            }
+            self.marked.clear();
+        } else if Some(key.clone()) == self.keybinds.plugin_break_out {
+            let targets = self.targets();
+            if !targets.is_empty() {
+                break_panes_to_new_tab(&targets, None, true);
+            }
+            self.marked.clear();
+        } else if Some(key.clone()) == self.keybinds.plugin_rename_panes {
+            self.start_rename();
+        } else if Some(key.clone()) == self.keybinds.plugin_toggle_fullscreen {
+            if let Some(pane) = self.selected_pane() {
+                focus_pane_with_id(pane.pane_id, true);
+                toggle_pane_id_fullscreen(pane.pane_id);
+                self.search_key.clear();
+                hide_self();
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_move_to_tab {
+            let targets = self.targets();
+            if !targets.is_empty() {
+                self.input = Some(Input::new(Action::MoveToTab(targets), ""));
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_move_star_up {
+            if let Some(pane_id) = self.selected_pane().map(|pane| pane.pane_id) {
+                self.stars.active_mut().move_up(&pane_id);
+                self.save_stars();
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_move_star_down {
+            if let Some(pane_id) = self.selected_pane().map(|pane| pane.pane_id) {
+                self.stars.active_mut().move_down(&pane_id);
+                self.save_stars();
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_toggle_preview {
+            self.show_preview = !self.show_preview;
+        } else if Some(key.clone()) == self.keybinds.plugin_collapse && self.tree_layout() {
+            self.set_collapsed(true);
+        } else if Some(key.clone()) == self.keybinds.plugin_expand && self.tree_layout() {
+            self.set_collapsed(false);
+        } else {
+            edit_line(&self.keybinds, &mut self.search_key, &key);
+        }
+    }
+
+    fn handle_tab_key(&mut self, key: KeyWithModifier) {
+        let selected = self.display_tabs.get(self.selected);
+        if Some(key.clone()) == self.keybinds.plugin_navigate_to {
+            if let Some(tab) = selected {
+                switch_tab_to(tab_index(tab.position));
+                self.search_key.clear();
+                hide_self();
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_hide {
+            self.search_key.clear();
+            hide_self();
+        } else if Some(key.clone()) == self.keybinds.plugin_rename_panes {
+            if let Some(tab) = selected {
+                self.input = Some(Input::new(Action::RenameTab(tab.position), &tab.name));
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_close_panes {
+            if let Some(tab) = selected {
+                close_tab_with_index(tab.position);
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_new_tab {
+            new_tab();
+        } else {
+            edit_line(&self.keybinds, &mut self.search_key, &key);
+        }
+    }
+
+    fn handle_session_key(&mut self, key: KeyWithModifier) {
+        if Some(key.clone()) == self.keybinds.plugin_navigate_to {
+            if let Some(pane) = self.selected_pane() {
+                switch_to_pane(pane);
+                self.search_key.clear();
+                hide_self();
+            }
+        } else if Some(key.clone()) == self.keybinds.plugin_hide {
+            self.search_key.clear();
+            hide_self();
+        } else {
+            edit_line(&self.keybinds, &mut self.search_key, &key);
+        }
+    }
+
+    /// Edit the input prompt with key, or close it without running the action.
+    fn edit_input(&mut self, key: KeyWithModifier) {
+        if Some(key.clone()) == self.keybinds.plugin_hide {
+            self.input = None;
+        } else if let Some(input) = self.input.as_mut() {
+            edit_line(&self.keybinds, &mut input.editor, &key);
+        }
+    }
+
+    fn pane_ids(&self) -> HashSet<PaneId> {
+        self.panes.iter().map(|p| p.pane_id).collect()
+    }
+
+    /// Whether the panes are listed as a tree of tabs.
+    fn tree_layout(&self) -> bool {
+        self.mode == Mode::Panes && self.config.layout == config::Layout::Tree
+    }
+
+    /// Return the selected row of the tree layout.
+    fn selected_row(&self) -> Option<Row> {
+        if self.tree_layout() {
+            self.display_rows.get(self.selected).copied()
+        } else {
+            None
+        }
+    }
+
+    /// Return the selected pane, None when a tab header is selected.
+    fn selected_pane(&self) -> Option<&Pane> {
+        if !self.tree_layout() {
+            return self.display_panes.get(self.selected);
+        }
+        match self.selected_row()? {
+            Row::Pane(index) => self.display_panes.get(index),
+            Row::Tab(_) => None,
+        }
+    }
+
+    /// Collapse or expand the tab of the selected row and select its header.
+    fn set_collapsed(&mut self, collapse: bool) {
+        let position = match self.selected_row() {
+            Some(Row::Tab(position)) => position,
+            Some(Row::Pane(index)) => self.display_panes[index].tab_position,
+            None => return,
+        };
+        if collapse {
+            self.collapsed.insert(position);
+        } else {
+            self.collapsed.remove(&position);
+        }
+
+        // Rows before the header do not change, so it keeps its index.
+        if let Some(i) = self
+            .display_rows
+            .iter()
+            .position(|row| *row == Row::Tab(position))
+        {
+            self.selected = i;
+        }
+    }
+
+    /// Return the number of listed panes, tabs, or tree rows.
+    fn display_len(&self) -> usize {
+        match self.mode {
+            Mode::Panes if self.tree_layout() => self.display_rows.len(),
+            Mode::Panes | Mode::Sessions => self.display_panes.len(),
+            Mode::Tabs => self.display_tabs.len(),
+        }
+    }
+
+    /// Keep the selection on a listed item and inside the viewport,
+    /// which is height rows including the header.
+    fn update_viewport(&mut self, height: usize) {
+        let len = self.display_len();
+        self.selected = min(self.selected, len.saturating_sub(1));
+        self.list_rows = height.saturating_sub(1);
+        self.scroll_offset = scroll_offset(self.scroll_offset, self.selected, self.list_rows, len);
+    }
+
+    /// Change what the picker lists, starting at the top of the list.
+    fn switch_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.selected = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Move the selection if key is one of the selection keys.
+    fn select_with_key(&mut self, key: &KeyWithModifier) -> bool {
+        let key = Some(key.clone());
+        if key == self.keybinds.plugin_select_down {
+            self.select_downward();
+        } else if key == self.keybinds.plugin_select_up {
+            self.select_upward();
+        } else if key == self.keybinds.plugin_page_down {
+            self.select_page_down();
+        } else if key == self.keybinds.plugin_page_up {
+            self.select_page_up();
+        } else if key == self.keybinds.plugin_select_first {
+            self.select_first();
+        } else if key == self.keybinds.plugin_select_last {
+            self.select_last();
+        } else {
+            return false;
+        }
+        true
+    }
+
+    fn select_downward(&mut self) {
+        let len = self.display_len();
+        if len > 0 {
+            self.selected = (self.selected + 1) % len;
+        }
+    }
+
+    fn select_upward(&mut self) {
+        let len = self.display_len();
+        if len > 0 {
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+
+    /// Move the selection one viewport down, stopping at the last item.
+    fn select_page_down(&mut self) {
+        let last = self.display_len().saturating_sub(1);
+        self.selected = min(self.selected + self.list_rows.max(1), last);
+    }
+
+    /// Move the selection one viewport up, stopping at the first item.
+    fn select_page_up(&mut self) {
+        self.selected = self.selected.saturating_sub(self.list_rows.max(1));
+    }
+
+    fn select_first(&mut self) {
+        self.selected = 0;
+    }
+
+    fn select_last(&mut self) {
+        self.selected = self.display_len().saturating_sub(1);
+    }
+}
+
+/// Apply key to editor if it is an editor key or a char without modifiers.
+/// Return false when the key is not for the editor.
+fn edit_line(
+    keybinds: &keybind::Keybinds,
+    editor: &mut editor::LineEditor,
+    key: &KeyWithModifier,
+) -> bool {
+    let key = Some(key.clone());
+    if key == keybinds.plugin_cursor_left {
+        editor.move_left();
+    } else if key == keybinds.plugin_cursor_right {
+        editor.move_right();
+    } else if key == keybinds.plugin_cursor_home {
+        editor.move_home();
+    } else if key == keybinds.plugin_cursor_end {
+        editor.move_end();
+    } else if key == keybinds.plugin_delete_backward {
+        editor.backspace();
+    } else if key == keybinds.plugin_delete_forward {
+        editor.delete();
+    } else if key == keybinds.plugin_delete_word {
+        editor.delete_word();
+    } else if key == keybinds.plugin_clear_search {
+        editor.clear();
+    } else if let Some(KeyWithModifier {
+        bare_key: BareKey::Char(c),
+        ..
+    }) = key.filter(KeyWithModifier::has_no_modifiers)
+    {
+        editor.insert(c);
+    } else {
+        return false;
+    }
+    true
+}
+
+/// Return the current time in milliseconds since the Unix epoch.
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Focus pane, switching to its session if it belongs to another one.
+fn switch_to_pane(pane: &Pane) {
+    match &pane.session {
+        Some(session) if session.is_resurrectable => switch_session(Some(&session.name)),
+        Some(session) if !session.is_current => switch_session_with_focus(
+            &session.name,
+            None,
+            Some((pane.id(), matches!(pane.pane_id, PaneId::Plugin(_)))),
+        ),
+        _ => focus_pane_with_id(pane.pane_id, true),
+    }
+}
+
+/// Return the pane that the picker lists for pane_info,
+/// or None for panes that cannot be focused and the plugin itself.
+fn list_pane(tab_info: &TabInfo, pane_info: &PaneInfo, plugin_id: Option<u32>) -> Option<Pane> {
+    if pane_info.is_plugin && Some(pane_info.id) == plugin_id {
+        return None;
+    }
+
+    if pane_info.is_suppressed || !pane_info.is_selectable {
+        return None;
+    }
+
+    let pane_id = if pane_info.is_plugin {
+        PaneId::Plugin(pane_info.id)
+    } else {
+        PaneId::Terminal(pane_info.id)
+    };
+
+    Some(
+        Pane::new(tab_info.name.clone(), pane_id, pane_info.title.clone())
+            .with_command(pane_info.terminal_command.clone())
+            .with_floating(pane_info.is_floating)
+            .with_exited(pane_info.exited)
+            .with_tab_position(tab_info.position),
+    )
+}
+
+/// Return the panes of the live sessions in tab order,
+/// followed by a row for each resurrectable session.
+fn session_panes(
+    sessions: &[SessionInfo],
+    resurrectable: &[(String, Duration)],
+    plugin_id: Option<u32>,
+) -> Vec<Pane> {
+    let mut panes = Vec::new();
+
+    for session in sessions {
+        let info = Session {
+            name: session.name.clone(),
+            is_current: session.is_current_session,
+            is_resurrectable: false,
+        };
+        // Pane IDs are only unique within a session, so the plugin is only skipped in its own.
+        let plugin_id = plugin_id.filter(|_| session.is_current_session);
+
+        for tab_info in &session.tabs {
+            let Some(pane_infos) = session.panes.panes.get(&tab_info.position) else {
+                continue;
+            };
+            panes.extend(
+                pane_infos
+                    .iter()
+                    .filter_map(|pane_info| list_pane(tab_info, pane_info, plugin_id))
+                    .map(|pane| pane.with_session(info.clone())),
+            );
+        }
+    }
+
+    panes.extend(
+        resurrectable
+            .iter()
+            .map(|(name, _)| Pane::resurrectable(name.clone())),
+    );
+    panes
+}
+
+/// Return the message that reports all configuration errors on one line.
+fn configuration_error(errors: &[String]) -> Option<String> {
+    match errors {
+        [] => None,
+        [error] => Some(format!("Configuration error: {}", error)),
+        _ => Some(format!("Configuration errors: {}", errors.join("; "))),
+    }
+}
+
+/// Return the index that switch_tab_to and rename_tab expect for the tab at position.
+/// Unlike TabInfo::position, they count from 1, like the default tab names;
+/// Zellij looks up the tab to rename at the index minus one.
+fn tab_index(position: usize) -> u32 {
+    position as u32 + 1
+}
+
+/// Return the index of the tab that name refers to.
+/// Tab names that are equal to name win over tab names that contain it, ignoring case.
+fn resolve_tab(tab_infos: &[TabInfo], name: &str) -> Option<usize> {
+    let name = name.to_lowercase();
+    let tab = tab_infos
+        .iter()
+        .find(|tab| tab.name.to_lowercase() == name)
+        .or_else(|| {
+            tab_infos
+                .iter()
+                .find(|tab| tab.name.to_lowercase().contains(&name))
+        })?;
+    Some(tab.position)
+}
+
+/// Return the offset of a viewport of rows items that shows selected,
+/// scrolling as little as possible from offset.
+fn scroll_offset(offset: usize, selected: usize, rows: usize, len: usize) -> usize {
+    if rows == 0 {
+        return 0;
+    }
+
+    let offset = if selected < offset {
+        selected
+    } else if selected >= offset + rows {
+        selected + 1 - rows
+    } else {
+        offset
+    };
+    min(offset, len.saturating_sub(rows))
+}
+
+/// Return the sorted char indices of haystack that match the pattern.
+///
+/// Non-ASCII haystacks are matched char by char because Utf32Str::new indexes
+/// graphemes, or bytes when every grapheme starts with an ASCII char,
+/// while the columns are located by their char offsets.
+fn match_indices(pattern: &Pattern, matcher: &mut Matcher, haystack: &str) -> Vec<usize> {
+    let chars: Vec<char> = haystack.chars().collect();
+    let haystack = if haystack.is_ascii() {
+        Utf32Str::Ascii(haystack.as_bytes())
+    } else {
+        Utf32Str::Unicode(&chars)
+    };
+    let mut indices = Vec::new();
+    pattern.indices(haystack, matcher, &mut indices);
+
+    indices.sort_unstable();
+    indices.dedup();
+    indices.into_iter().map(|i| i as usize).collect()
+}
+
+/// Return the headers of columns, clipped and padded to widths.
+fn column_headers(columns: &[columns::ColumnSpec], widths: &[usize]) -> Vec<String> {
+    columns
+        .iter()
+        .zip(widths)
+        .map(|(spec, &width)| {
+            let title = text::clip(spec.column.header(), width, text::Ellipsis::End);
+            text::pad(&title.text, width)
+        })
+        .collect()
+}
+
+/// Return what a CLI pipe prints for the result of its command.
+/// Unknown commands broadcast to all plugins are left to the other plugins and yield None,
+/// but pipes sent to this plugin always get a reply so that the CLI does not wait forever.
+fn command_output(
+    result: Result<String, command::CommandError>,
+    is_private: bool,
+) -> Option<String> {
+    match result {
+        Err(command::CommandError::Unknown(_)) if !is_private => None,
+        Ok(output) => Some(output),
+        Err(err) => Some(format!("error: {}\n", err)),
+    }
+}
+
+/// Highlight the matched chars of the original content that are still visible
+/// after it was clipped.
+fn color_matches<I>(text: Text, color: Option<usize>, clipped: &text::Clipped, indices: I) -> Text
+where
+    I: Iterator<Item = usize>,
+{
+    theme::color_indices(text, color, clipped.indices(indices))
+}
+
+#[cfg(not(test))]
+register_plugin!(State);
+
+impl ZellijPlugin for State {
+    fn load(&mut self, configuration: BTreeMap<String, String>) {
+        let (keybinds, keybind_errors) = keybind::Keybinds::parse(&configuration);
+        let (config, config_errors) = config::Config::parse(&configuration);
+        self.keybinds = keybinds;
+        self.config = config;
+        let errors: Vec<String> = keybind_errors
+            .iter()
+            .map(ToString::to_string)
+            .chain(config_errors.iter().map(ToString::to_string))
+            .collect();
+        self.error = configuration_error(&errors);
+        self.stars = star::StarGroups::new(self.config.star_groups.clone());
+        self.focus_history = focus::FocusHistory::new(self.config.focus_history_size);
+        self.plugin_id = Some(get_plugin_ids().plugin_id);
+        self.history = history::SearchHistory::new(self.config.search_history_size);
+        self.history_path = Some(persist::data_file("history"));
+        self.load_history();
+        if self.config.global_stars {
+            self.global_stars_path = Some(persist::data_file("global_stars"));
+            self.load_global_stars();
+        }
+
+        request_permission(&[
+            PermissionType::ChangeApplicationState,
+            PermissionType::ReadApplicationState,
+            PermissionType::Reconfigure,
+        ]);
+
+        subscribe(&[
+            EventType::ModeUpdate,
+            EventType::Key,
+            EventType::PaneUpdate,
+            EventType::TabUpdate,
+            EventType::PastedText,
+            EventType::SessionUpdate,
+        ]);
+    }
+
+    fn update(&mut self, event: Event) -> bool {
+        match event {
+            Event::ModeUpdate(mode_info) => {
+                self.session_name = mode_info.session_name;
+                self.update_stars_path();
+                if let Some(base_mode) = mode_info.base_mode {
+                    if !self.bound_key {
+                        if let Some(plugin_id) = self.plugin_id {
+                            self.keybinds
+                                .bind_global_keys(base_mode, plugin_id, reconfigure);
+                        }
+                    }
+                }
+            }
+            Event::TabUpdate(tab_infos) => {
+                self.tab_infos = tab_infos;
+                self.update_state();
+            }
+            Event::PaneUpdate(PaneManifest { panes }) => {
+                self.pane_infos = panes;
+                self.update_state();
+            }
+            Event::SessionUpdate(sessions, resurrectable) => {
+                self.session_panes = session_panes(&sessions, &resurrectable, self.plugin_id);
+            }
+            Event::Key(key) if self.input.is_some() => {
+                if Some(key.clone()) == self.keybinds.plugin_navigate_to {
+                    if let Some(input) = self.input.take() {
+                        self.submit_input(input);
+                    }
+                } else {
+                    self.edit_input(key);
+                }
+            }
+            Event::Key(key) => {
+                if self.select_with_key(&key) {
+                    // The selection moved.
+                } else if Some(key.clone()) == self.keybinds.plugin_switch_mode {
+                    self.switch_mode(self.mode.next());
+                } else {
+                    match self.mode {
+                        Mode::Panes => self.handle_pane_key(key),
+                        Mode::Tabs => self.handle_tab_key(key),
+                        Mode::Sessions => self.handle_session_key(key),
+                    }
+                }
+            }
+            Event::PastedText(text) => match self.input.as_mut() {
+                Some(input) => input.editor.insert_str(&text),
+                None => self.search_key.insert_str(&text),
+            },
+            _ => {}
+        }
+        true
+    }
+
+    fn pipe(&mut self, pipe_message: PipeMessage) -> bool {
+        if pipe_message.source == PipeSource::Keybind && pipe_message.is_private {
+            if pipe_message.name == keybind::LIST_PANES {
+                self.switch_mode(Mode::Panes);
+                if self.config.prefill_last_query && self.search_key.text().is_empty() {
+                    if let Some(query) = self.history.last() {
+                        self.search_key = editor::LineEditor::from(query);
+                    }
+                }
+                show_self(true);
+            } else if pipe_message.name == keybind::LIST_TABS {
+                self.switch_mode(Mode::Tabs);
+                show_self(true);
+            } else if pipe_message.name == keybind::LIST_SESSIONS {
+                self.switch_mode(Mode::Sessions);
+                show_self(true);
+            } else if pipe_message.name == keybind::NAVIGATE_BACK {
+                if let Some(id) = self.focus_history.previous() {
+                    focus_pane_with_id(id, true);
+                }
+            } else if pipe_message.name == keybind::FOCUS_BACK {
+                if let Some(id) = self.focus_history.back(&self.pane_ids()) {
+                    focus_pane_with_id(id, true);
+                }
+            } else if pipe_message.name == keybind::FOCUS_FORWARD {
+                if let Some(id) = self.focus_history.forward(&self.pane_ids()) {
+                    focus_pane_with_id(id, true);
+                }
+            } else if pipe_message.name == keybind::TOGGLE_STAR {
+                if let Some(pane_id) = self.focus_history.current() {
+                    self.toggle_star(pane_id);
+                }
+            } else if pipe_message.name == keybind::NEXT_STAR {
+                self.focus_next_star(true);
+            } else if pipe_message.name == keybind::PREV_STAR {
+                self.focus_next_star(false);
+            } else if pipe_message.name == keybind::JUMP {
+                if let Some(query) = pipe_message.payload.as_deref() {
+                    self.jump(query);
+                }
+            } else if pipe_message.name == keybind::NEXT_STAR_GROUP {
+                self.stars.next_group();
+            } else if pipe_message.name == keybind::PREV_STAR_GROUP {
+                self.stars.previous_group();
+            } else if let Some(slot) = keybind::FOCUS_SLOTS
+                .iter()
+                .position(|name| *name == pipe_message.name)
+            {
+                if let Some(id) = self.stars.active().get(slot) {
+                    focus_pane_with_id(*id, true);
+                }
+            }
+            return true;
+        }
+
+        if let PipeSource::Cli(pipe_id) = &pipe_message.source {
+            let result =
+                command::Command::parse(&pipe_message.name, pipe_message.payload.as_deref())
+                    .and_then(|command| self.run_command(command));
+            let Some(output) = command_output(result, pipe_message.is_private) else {
+                return false;
+            };
+            cli_pipe_output(pipe_id, &output);
+            unblock_cli_pipe_input(pipe_id);
+            return true;
+        }
+        false
+    }
+
+    fn render(&mut self, rows: usize, cols: usize) {
+        match frame::Frame::new(rows, cols, self.show_preview) {
+            frame::Frame::Table { prompt, layout } => self.render_table(prompt, layout),
+            frame::Frame::Compact { prompt, list } => self.render_compact(prompt, list),
+            frame::Frame::TooSmall => {
+                if rows > 0 {
+                    let message = text::clip("Too small", cols, text::Ellipsis::End);
+                    print_text_with_coordinates(Text::new(message.text), 0, 0, Some(cols), Some(1));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn panes_as_table() {
+        let mut state = State {
+            tab_infos: vec![
+                TabInfo {
+                    name: String::from("Tab 1"),
+                    ..Default::default()
+                },
+                TabInfo {
+                    name: String::from("Tab 2"),
+                    ..Default::default()
+                },
+            ],
+            pane_infos: HashMap::from([
+                (
+                    // This will be the last because it is the last tab.
+                    1,
+                    vec![PaneInfo {
+                        id: 55,
+                        title: String::from("Pane 55"),
+                        is_selectable: true,
+                        ..Default::default()
+                    }],
+                ),
+                (
+                    0,
+                    vec![
+                        PaneInfo {
+                            id: 1,
+                            title: String::from("Pane 1"),
+                            is_selectable: true,
+                            ..Default::default()
+                        },
+                        PaneInfo {
+                            id: 2,
+                            title: String::from("Pane 2"),
+                            is_selectable: true,
+                            ..Default::default()
+                        },
+                        // Hidden because is not selectable
+                        PaneInfo {
+                            id: 3,
+                            title: String::from("Pane 3 (not selectable)"),
+                            ..Default::default()
+                        },
+                        // Hidden because is suppressed
+                        PaneInfo {
+                            id: 4,
+                            title: String::from("Pane 4 (suppressed)"),
+                            is_selectable: true,
+                            is_suppressed: true,
+                            ..Default::default()
+                        },
+                        // Hidden because is the plugin
+                        PaneInfo {
+                            id: 4,
+                            title: String::from("Pane 4 (suppressed)"),
+                            is_selectable: true,
+                            is_plugin: true,
+                            ..Default::default()
+                        },
+                    ],
+                ),
+                (
+                    // The following tab does not exist.
+                    2,
+                    vec![PaneInfo {
+                        id: 99,
+                        title: String::from("Pane 99 on non existing tab"),
+                        is_selectable: true,
+                        ..Default::default()
+                    }],
+                ),
+            ]),
+            selected: 1,
+            plugin_id: Some(4),
+            ..Default::default()
+        };
+
+        state.stars.active_mut().toggle(PaneId::Terminal(2));
+        state.update_state();
+
+        // The table takes 22 columns: the star, "Tab 1", " ID", and "Pane Title",
+        // separated by a space each.
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(22, 20).serialize()
+        ));
+    }
+
+    #[test]
+    fn panes_as_table_highlight_matches() {
+        let mut state = State {
+            tab_infos: vec![tab("editor"), tab("server")],
+            pane_infos: HashMap::from([
+                (
+                    0,
+                    vec![PaneInfo {
+                        title: String::from("vim src/main.rs"),
+                        ..pane(1)
+                    }],
+                ),
+                (
+                    1,
+                    vec![
+                        PaneInfo {
+                            title: String::from("cargo run"),
+                            ..pane(2)
+                        },
+                        PaneInfo {
+                            title: String::from("a very long title that ends with vim"),
+                            ..pane(3)
+                        },
+                    ],
+                ),
+            ]),
+            search_key: editor::LineEditor::from("e vim"),
+            ..Default::default()
+        };
+        state.update_state();
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(30, 20).serialize()
+        ));
+    }
+
+    #[rstest]
+    #[case::id_is_not_free_text("12", vec![PaneId::Terminal(1)])]
+    #[case::id_filter("id:12", vec![PaneId::Terminal(12)])]
+    #[case::tab_filter("tab:2", vec![PaneId::Terminal(12), PaneId::Plugin(3)])]
+    #[case::filter_and_text("is:terminal pane", vec![PaneId::Terminal(1), PaneId::Terminal(12)])]
+    #[case::cmd_filter("cmd:cargo", vec![PaneId::Terminal(12)])]
+    #[case::floating_filter("is:floating", vec![PaneId::Plugin(3)])]
+    #[case::starred_filter("is:starred", vec![PaneId::Terminal(1)])]
+    #[case::command_text("watch", vec![PaneId::Terminal(12)])]
+    fn panes_as_table_query(#[case] search_key: &str, #[case] expected: Vec<PaneId>) {
+        let mut state = State {
+            tab_infos: vec![tab("Tab 1"), tab("Tab 2")],
+            pane_infos: HashMap::from([
+                (
+                    0,
+                    vec![PaneInfo {
+                        title: String::from("Pane for issue 12"),
+                        ..pane(1)
+                    }],
+                ),
+                (
+                    1,
+                    vec![
+                        PaneInfo {
+                            title: String::from("Pane"),
+                            terminal_command: Some(String::from("cargo watch")),
+                            ..pane(12)
+                        },
+                        PaneInfo {
+                            title: String::from("Plugin"),
+                            is_plugin: true,
+                            is_floating: true,
+                            ..pane(3)
+                        },
+                    ],
+                ),
+            ]),
+            search_key: editor::LineEditor::from(search_key),
+            ..Default::default()
+        };
+        state.stars.active_mut().toggle(PaneId::Terminal(1));
+        state.update_state();
+
+        state.panes_as_table(40, 20);
+
+        let got: Vec<PaneId> = state.display_panes.iter().map(|p| p.pane_id).collect();
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn panes_as_table_with_groups() {
+        let mut state = State {
+            tab_infos: vec![tab("Tab")],
+            pane_infos: HashMap::from([(0, vec![pane(1), pane(2), pane(3)])]),
+            stars: star::StarGroups::new(vec![String::from("front"), String::from("back")]),
+            ..Default::default()
+        };
+
+        state.stars.active_mut().toggle(PaneId::Terminal(1));
+        state.stars.active_mut().toggle(PaneId::Terminal(2));
+        state.stars.next_group();
+        state.stars.active_mut().toggle(PaneId::Terminal(2));
+        state.update_state();
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(40, 20).serialize()
+        ));
+    }
+
+    #[test]
+    fn panes_as_table_with_command() {
+        let mut state = State {
+            tab_infos: vec![tab("api")],
+            pane_infos: HashMap::from([(
+                0,
+                vec![
+                    PaneInfo {
+                        title: String::from("Pane"),
+                        terminal_command: Some(String::from("cargo watch -x test")),
+                        ..pane(1)
+                    },
+                    PaneInfo {
+                        title: String::from("a long title of a shell pane"),
+                        ..pane(2)
+                    },
+                ],
+            )]),
+            search_key: editor::LineEditor::from("watch"),
+            ..Default::default()
+        };
+        state.update_state();
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(40, 20).serialize()
+        ));
+    }
+
+    #[fixture]
+    fn tree_state() -> State {
+        State {
+            tab_infos: vec![
+                TabInfo {
+                    position: 0,
+                    ..tab("editor")
+                },
+                TabInfo {
+                    position: 1,
+                    ..tab("api")
+                },
+            ],
+            pane_infos: HashMap::from([
+                (
+                    0,
+                    vec![
+                        PaneInfo {
+                            title: String::from("vim"),
+                            ..pane(1)
+                        },
+                        PaneInfo {
+                            title: String::from("shell"),
+                            ..pane(2)
+                        },
+                    ],
+                ),
+                (
+                    1,
+                    vec![
+                        PaneInfo {
+                            title: String::from("server logs"),
+                            ..pane(3)
+                        },
+                        PaneInfo {
+                            title: String::from("shell"),
+                            ..pane(4)
+                        },
+                    ],
+                ),
+            ]),
+            config: config::Config {
+                layout: config::Layout::Tree,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    fn tree_as_table(mut tree_state: State) {
+        tree_state.update_state();
+        tree_state.collapsed.insert(0);
+        tree_state.selected = 2;
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            tree_state.tree_as_table(40, 20).serialize()
+        ));
+        assert_eq!(
+            tree_state.selected_pane().map(|pane| pane.pane_id),
+            Some(PaneId::Terminal(3))
+        );
+    }
+
+    #[rstest]
+    fn tree_as_table_with_columns(mut tree_state: State) {
+        tree_state.config.columns = columns::parse("kind,tab,title:flex,id").unwrap();
+        tree_state.search_key = editor::LineEditor::from("shell");
+        tree_state.update_state();
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            tree_state.tree_as_table(40, 20).serialize()
+        ));
+    }
+
+    #[rstest]
+    #[case::all_fit(40, &[Column::Star, Column::Id, Column::Title])]
+    #[case::id_hidden(12, &[Column::Star, Column::Title])]
+    #[case::only_title(11, &[Column::Title])]
+    fn tree_columns_fit(mut tree_state: State, #[case] width: usize, #[case] expected: &[Column]) {
+        tree_state.update_state();
+
+        let (columns, widths) = tree_state.pane_columns(&tree_state.panes, width, "  ");
+
+        let got: Vec<Column> = columns.iter().map(|spec| spec.column).collect();
+        assert_eq!(got, expected);
+        assert!(widths.iter().sum::<usize>() + widths.len() - 1 <= width);
+    }
+
+    #[rstest]
+    fn tree_as_table_groups_matches_by_tab(mut tree_state: State) {
+        tree_state.update_state();
+        tree_state.search_key = editor::LineEditor::from("logs");
+        tree_state.tree_as_table(40, 20);
+
+        assert_eq!(tree_state.display_rows, vec![Row::Tab(1), Row::Pane(0)]);
+
+        tree_state.search_key = editor::LineEditor::from("shell");
+        tree_state.tree_as_table(40, 20);
+
+        assert_eq!(
+            tree_state.display_rows,
+            vec![Row::Tab(0), Row::Pane(0), Row::Tab(1), Row::Pane(1)]
+        );
+    }
+
+    #[rstest]
+    fn collapse_and_expand(mut tree_state: State) {
+        tree_state.update_state();
+        tree_state.tree_as_table(40, 20);
+        tree_state.selected = 2;
+
+        tree_state.set_collapsed(true);
+        tree_state.tree_as_table(40, 20);
+
+        assert_eq!(tree_state.selected, 0);
+        assert!(tree_state.selected_pane().is_none());
+        assert_eq!(
+            tree_state.display_rows,
+            vec![Row::Tab(0), Row::Tab(1), Row::Pane(2), Row::Pane(3)]
+        );
+
+        tree_state.set_collapsed(false);
+        tree_state.tree_as_table(40, 20);
+
+        assert_eq!(tree_state.selected, 0);
+        assert_eq!(tree_state.display_rows.len(), 6);
+    }
+
+    #[rstest]
+    fn collapsed_tabs_are_pruned(mut tree_state: State) {
+        tree_state.collapsed = HashSet::from([1, 5]);
+
+        tree_state.update_state();
+
+        assert_eq!(tree_state.collapsed, HashSet::from([1]));
+    }
+
+    #[rstest]
+    fn compact_lines(mut tree_state: State) {
+        tree_state.update_state();
+        tree_state.collapsed.insert(0);
+        tree_state.stars.active_mut().toggle(PaneId::Terminal(3));
+        tree_state.tree_as_table(40, 3);
+
+        assert_eq!(
+            tree_state.compact_lines(),
+            ["▸ 1 editor (2)", "▾ 2 api (2)"]
+        );
+
+        tree_state.config.layout = config::Layout::Flat;
+        tree_state.selected = 3;
+        tree_state.panes_as_table(40, 3);
+
+        assert_eq!(
+            tree_state.compact_lines(),
+            ["1 server logs (api)", "  shell (api)"]
+        );
+
+        tree_state.switch_mode(Mode::Tabs);
+        tree_state.tabs_as_table(40, 20);
+
+        assert_eq!(tree_state.compact_lines(), ["editor", "api"]);
+    }
+
+    #[rstest]
+    fn tables_fit_small_panes(mut tree_state: State, sessions: Vec<SessionInfo>) {
+        tree_state.update_state();
+        tree_state.session_panes = session_panes(&sessions, &[], None);
+        tree_state.selected = 3;
+        for width in 0..=60 {
+            for height in 0..=10 {
+                for (mode, layout) in [
+                    (Mode::Panes, config::Layout::Tree),
+                    (Mode::Panes, config::Layout::Flat),
+                    (Mode::Tabs, config::Layout::Flat),
+                    (Mode::Sessions, config::Layout::Flat),
+                ] {
+                    tree_state.mode = mode;
+                    tree_state.config.layout = layout;
+                    let (table, _) = tree_state.build_table(width, height);
+
+                    // The tab markers and their separator take two columns in any case.
+                    let widths = column_widths(&table);
+                    let total = widths.iter().sum::<usize>() + widths.len().saturating_sub(1);
+                    assert!(
+                        total <= width.max(2),
+                        "{:?} {:?} at width {}: {:?}",
+                        mode,
+                        layout,
+                        width,
+                        widths
+                    );
+
+                    let lines = tree_state.compact_lines();
+                    assert!(lines.len() <= height.saturating_sub(1));
+                    if !lines.is_empty() {
+                        assert!(tree_state.selected >= tree_state.scroll_offset);
+                        assert!(tree_state.selected < tree_state.scroll_offset + lines.len());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn panes_as_table_with_columns() {
+        let mut state = State {
+            tab_infos: vec![tab("api")],
+            pane_infos: HashMap::from([(
+                0,
+                vec![
+                    pane(1),
+                    PaneInfo {
+                        title: String::from("status-bar"),
+                        is_plugin: true,
+                        ..pane(2)
+                    },
+                ],
+            )]),
+            config: config::Config {
+                columns: columns::parse("slot,kind,title:6,id").unwrap(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.update_state();
+        state.stars.active_mut().toggle(PaneId::Plugin(2));
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(40, 20).serialize()
+        ));
+    }
+
+    #[test]
+    fn panes_as_table_with_theme() {
+        let mut state = State {
+            tab_infos: vec![tab("api")],
+            pane_infos: HashMap::from([(
+                0,
+                vec![
+                    PaneInfo {
+                        title: String::from("vim"),
+                        ..pane(1)
+                    },
+                    PaneInfo {
+                        title: String::from("shell"),
+                        ..pane(2)
+                    },
+                    PaneInfo {
+                        title: String::from("build"),
+                        exited: true,
+                        ..pane(3)
+                    },
+                ],
+            )]),
+            config: config::Config {
+                theme: theme::Theme {
+                    current_pane: Some(2),
+                    previous_pane: Some(0),
+                    header: Some(1),
+                    dimmed: Some(3),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.update_state();
+        state
+            .focus_history
+            .visit(PaneId::Terminal(2), &state.pane_ids());
+        state
+            .focus_history
+            .visit(PaneId::Terminal(1), &state.pane_ids());
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(30, 20).serialize()
+        ));
+    }
+
+    #[rstest]
+    fn tables_with_theme(mut tree_state: State, sessions: Vec<SessionInfo>) {
+        tree_state.config.theme = theme::Theme {
+            header: Some(1),
+            dimmed: Some(2),
+            mark: Some(3),
+            session: Some(0),
+            ..Default::default()
+        };
+        tree_state.update_state();
+        tree_state.marked.insert(PaneId::Terminal(2));
+
+        insta::assert_snapshot!(
+            "tree_with_theme",
+            format!(
+                "\u{1b}Pztable;{}",
+                tree_state.tree_as_table(40, 20).serialize()
+            )
+        );
+
+        tree_state.tab_infos[1].active = true;
+        tree_state.update_state();
+        tree_state.switch_mode(Mode::Tabs);
+
+        insta::assert_snapshot!(
+            "tabs_with_theme",
+            format!(
+                "\u{1b}Pztable;{}",
+                tree_state.tabs_as_table(40, 20).serialize()
+            )
+        );
+
+        tree_state.session_panes = session_panes(
+            &sessions,
+            &[(String::from("blog"), Duration::from_secs(60))],
+            Some(2),
+        );
+        tree_state.switch_mode(Mode::Sessions);
+
+        insta::assert_snapshot!(
+            "sessions_with_theme",
+            format!(
+                "\u{1b}Pztable;{}",
+                tree_state.panes_as_table(60, 20).serialize()
+            )
+        );
+    }
+
+    #[test]
+    fn highlight_clipped_combining_mark() {
+        let title = "cafe\u{301} crème logs";
+        let pattern = Pattern::parse("cafe logs", CaseMatching::Ignore, Normalization::Smart);
+        let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+
+        let indices = match_indices(&pattern, &mut matcher, title);
+        let clipped = text::clip(title, 12, text::Ellipsis::Middle);
+
+        assert_eq!(clipped.text, "cafe\u{301} ...logs");
+        // The combining mark is highlighted with its letter and the tail keeps its matches.
+        assert_eq!(clipped.indices(indices), [0, 1, 2, 3, 4, 9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn type_combined_query() {
+        let keybinds = keybind::Keybinds::default();
+        let mut editor = editor::LineEditor::default();
+
+        for c in "tab:api logs".chars() {
+            let key = KeyWithModifier::new(BareKey::Char(c));
+            // handle_pane_key checks the star toggle before the prompt gets the key.
+            assert_ne!(Some(key.clone()), keybinds.plugin_toggle_star);
+            edit_line(&keybinds, &mut editor, &key);
+        }
+
+        assert_eq!(editor.text(), "tab:api logs");
+    }
+
+    #[rstest]
+    #[case::ascii("Tab", "server logs", "logs", &[7, 8, 9, 10])]
+    #[case::combining_mark_in_tab("cafe\u{301}", "logs", "logs", &[0, 1, 2, 3])]
+    #[case::combining_mark_in_title("Tab", "cafe\u{301} logs", "logs", &[6, 7, 8, 9])]
+    #[case::zwj_emoji_in_tab("👩‍💻", "logs", "logs", &[0, 1, 2, 3])]
+    #[case::zwj_emoji_in_title("Tab", "👩‍💻 logs", "logs", &[4, 5, 6, 7])]
+    #[case::cjk_in_tab("日本語", "logs", "logs", &[0, 1, 2, 3])]
+    fn title_match_indices(
+        #[case] tab_name: &str,
+        #[case] title: &str,
+        #[case] query: &str,
+        #[case] expected: &[usize],
+    ) {
+        let pane = Pane::new(
+            String::from(tab_name),
+            PaneId::Terminal(1),
+            String::from(title),
+        );
+        let pattern = Pattern::parse(query, CaseMatching::Ignore, Normalization::Smart);
+        let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
+
+        let got: Vec<usize> = match_indices(&pattern, &mut matcher, &pane.search_string)
+            .into_iter()
+            .filter(|i| (pane.title_start()..pane.command_start()).contains(i))
+            .map(|i| i - pane.title_start())
+            .collect();
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn panes_as_table_with_wide_chars() {
+        let mut state = State {
+            tab_infos: vec![tab("日本語")],
+            pane_infos: HashMap::from([(
+                0,
+                vec![
+                    PaneInfo {
+                        title: String::from("~/src/プロジェクト/main.rs"),
+                        ..pane(1)
+                    },
+                    PaneInfo {
+                        title: String::from("café crème"),
+                        ..pane(2)
+                    },
+                ],
+            )]),
+            search_key: editor::LineEditor::from("rs"),
+            config: config::Config {
+                ellipsis: text::Ellipsis::Middle,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.update_state();
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(30, 20).serialize()
+        ));
+    }
+
+    #[test]
+    fn panes_as_table_sort_by_mru() {
+        let mut state = State {
+            tab_infos: vec![tab("Tab")],
+            pane_infos: HashMap::from([(0, vec![pane(1), pane(2), pane(3), pane(4)])]),
+            config: config::Config {
+                sort_by: config::SortBy::Mru,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.update_state();
+        state
+            .focus_history
+            .visit(PaneId::Terminal(3), &state.pane_ids());
+        state
+            .focus_history
+            .visit(PaneId::Terminal(2), &state.pane_ids());
+
+        state.panes_as_table(20, 20);
+
+        let got: Vec<PaneId> = state.display_panes.iter().map(|p| p.pane_id).collect();
+        assert_eq!(
+            got,
+            vec![
+                PaneId::Terminal(2),
+                PaneId::Terminal(3),
+                PaneId::Terminal(1),
+                PaneId::Terminal(4)
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::empty_search("", vec![2, 3, 1, 4])]
+    #[case::search("Pane", vec![2, 3, 1, 4])]
+    fn panes_as_table_sort_by_frecency(#[case] search_key: &str, #[case] expected: Vec<u32>) {
+        let mut state = State {
+            tab_infos: vec![active_tab("Tab")],
+            search_key: editor::LineEditor::from(search_key),
+            config: config::Config {
+                sort_by: config::SortBy::Frecency,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for focused in [3, 2, 3, 2] {
+            state.pane_infos = HashMap::from([(
+                0,
+                (1..=4)
+                    .map(|id| PaneInfo {
+                        title: format!("Pane {}", id),
+                        is_focused: id == focused,
+                        ..pane(id)
+                    })
+                    .collect(),
+            )]);
+            state.update_state();
+        }
+
+        state.panes_as_table(20, 20);
+
+        let got: Vec<PaneId> = state.display_panes.iter().map(|p| p.pane_id).collect();
+        assert_eq!(
+            got,
+            expected
+                .into_iter()
+                .map(PaneId::Terminal)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn restore_stars() {
+        let mut state = State {
+            tab_infos: vec![tab("Tab 1"), tab("Tab 2")],
+            pane_infos: HashMap::from([
+                (
+                    0,
+                    vec![PaneInfo {
+                        title: String::from("vim"),
+                        ..pane(1)
+                    }],
+                ),
+                (
+                    1,
+                    vec![PaneInfo {
+                        title: String::from("vim"),
+                        ..pane(2)
+                    }],
+                ),
+            ]),
+            ..Default::default()
+        };
+        state.stars.set_pending(
+            star::decode(
+                "default\tterminal\tTab 2\tvim\ndefault\tterminal\tTab 3\tvim\n",
+                star::DEFAULT_GROUP,
+            ),
+            now_millis(),
+        );
+
+        state.update_state();
+
+        assert!(!state.stars.active().has(&PaneId::Terminal(1)));
+        assert!(state.stars.active().has(&PaneId::Terminal(2)));
+        assert_eq!(
+            state.stars.entries(|_| None),
+            star::decode("default\tterminal\tTab 3\tvim\n", star::DEFAULT_GROUP)
+        );
+    }
+
+    #[test]
+    fn panes_as_table_viewport() {
+        let mut state = State {
+            tab_infos: vec![tab("Tab")],
+            pane_infos: HashMap::from([(0, (1..=10).map(pane).collect())]),
+            selected: 7,
+            ..Default::default()
+        };
+        state.update_state();
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(20, 4).serialize()
+        ));
+        assert_eq!(state.scroll_offset, 5);
+        assert_eq!(state.list_rows, 3);
+
+        // The selection is kept on the panes that match.
+        state.search_key = editor::LineEditor::from("id:2");
+        state.panes_as_table(20, 4);
+        assert_eq!(state.selected, 0);
+        assert_eq!(state.scroll_offset, 0);
+    }
+
+    #[rstest]
+    #[case::visible(2, 3, 5, 20, 2)]
+    #[case::above(4, 1, 5, 20, 1)]
+    #[case::below(0, 7, 5, 20, 3)]
+    #[case::end_of_list(15, 18, 5, 18, 13)]
+    #[case::fits(3, 2, 5, 4, 0)]
+    #[case::no_rows(3, 2, 0, 4, 0)]
+    fn scroll_offset_keeps_selected_visible(
+        #[case] offset: usize,
+        #[case] selected: usize,
+        #[case] rows: usize,
+        #[case] len: usize,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(scroll_offset(offset, selected, rows, len), expected);
+    }
+
+    #[rstest]
+    fn select_by_page(display_panes: Vec<Pane>) {
+        let mut state = State {
+            display_panes,
+            list_rows: 2,
+            ..Default::default()
+        };
+
+        state.select_page_down();
+        assert_eq!(state.selected, 1);
+        state.select_page_up();
+        assert_eq!(state.selected, 0);
+        state.select_last();
+        assert_eq!(state.selected, 1);
+        state.select_first();
+        assert_eq!(state.selected, 0);
+    }
+
+    #[fixture]
+    fn marked_state() -> State {
+        let mut state = State {
+            tab_infos: vec![tab("Tab")],
+            pane_infos: HashMap::from([(
+                0,
+                (1..=4)
+                    .map(|id| PaneInfo {
+                        title: format!("Pane {}", id),
+                        ..pane(id)
+                    })
+                    .collect(),
+            )]),
+            ..Default::default()
+        };
+        state.update_state();
+        state.panes_as_table(40, 20);
+        state
+    }
+
+    #[rstest]
+    fn marks(marked_state: State) {
+        let mut state = marked_state;
+
+        assert_eq!(state.targets(), vec![PaneId::Terminal(1)]);
+
+        state.select_downward();
+        state.toggle_mark();
+        state.toggle_mark();
+        assert_eq!(state.selected, 3);
+
+        // Marks survive filtering.
+        state.search_key = editor::LineEditor::from("Pane 4");
+        state.panes_as_table(40, 20);
+        state.toggle_mark();
+        assert_eq!(
+            state.targets(),
+            vec![
+                PaneId::Terminal(2),
+                PaneId::Terminal(3),
+                PaneId::Terminal(4)
+            ]
+        );
+
+        // Marking again unmarks.
+        state.toggle_mark();
+        assert_eq!(
+            state.targets(),
+            vec![PaneId::Terminal(2), PaneId::Terminal(3)]
+        );
+
+        // Marks of closed panes are pruned.
+        state.pane_infos.get_mut(&0).unwrap().retain(|p| p.id != 3);
+        state.update_state();
+        assert_eq!(state.targets(), vec![PaneId::Terminal(2)]);
+    }
+
+    #[rstest]
+    fn panes_as_table_with_marks(marked_state: State) {
+        let mut state = marked_state;
+        state.marked.insert(PaneId::Terminal(2));
+
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.panes_as_table(30, 20).serialize()
+        ));
+    }
+
+    #[rstest]
+    #[case::star(Some(true), ["starred\n", "starred\n"])]
+    #[case::unstar(Some(false), ["unstarred\n", "unstarred\n"])]
+    #[case::toggle(None, ["starred\n", "unstarred\n"])]
+    fn star_pane_twice(
+        marked_state: State,
+        #[case] starred: Option<bool>,
+        #[case] expected: [&str; 2],
+    ) {
+        let mut state = marked_state;
+        let pane_id = PaneId::Terminal(2);
+
+        for expected in expected {
+            assert_eq!(state.star_pane(pane_id, starred).unwrap(), expected);
+            assert_eq!(state.stars.active().has(&pane_id), expected == "starred\n");
+        }
+        assert_eq!(
+            state.star_pane(PaneId::Terminal(99), starred),
+            Err(command::CommandError::NoSuchPane(String::from(
+                "terminal_99"
+            )))
+        );
+    }
+
+    #[rstest]
+    fn toggle_star_targets(marked_state: State) {
+        let mut state = marked_state;
+        state.stars.active_mut().toggle(PaneId::Terminal(2));
+        state.marked = HashSet::from([PaneId::Terminal(2), PaneId::Terminal(3)]);
+
+        // Star all when some are not starred.
+        state.toggle_star_targets();
+        assert!(state.stars.active().has(&PaneId::Terminal(2)));
+        assert!(state.stars.active().has(&PaneId::Terminal(3)));
+
+        // Unstar all when all are starred.
+        state.toggle_star_targets();
+        assert!(!state.stars.active().has(&PaneId::Terminal(2)));
+        assert!(!state.stars.active().has(&PaneId::Terminal(3)));
+    }
+
+    #[test]
+    fn edit_input() {
+        let mut state = State {
+            input: Some(Input::new(Action::RenamePanes(vec![]), "vi")),
+            ..Default::default()
+        };
+
+        state.edit_input(KeyWithModifier::new(BareKey::Char('m')));
+        assert_eq!(state.input.as_ref().unwrap().editor.text(), "vim");
+        assert_eq!(state.search_key.text(), "");
+
+        state.edit_input(KeyWithModifier::new(BareKey::Esc));
+        assert!(state.input.is_none());
+    }
+
+    #[rstest]
+    fn list_stars(marked_state: State) {
+        let mut state = marked_state;
+        state.stars.active_mut().toggle(PaneId::Terminal(3));
+        state.stars.active_mut().toggle(PaneId::Terminal(1));
+
+        assert_eq!(
+            state.list_stars(),
+            "1\tterminal_3\tTab\tPane 3\n2\tterminal_1\tTab\tPane 1\n"
+        );
+    }
+
+    #[rstest]
+    fn list_panes(marked_state: State) {
+        let mut state = marked_state;
+        state.pane_infos.get_mut(&0).unwrap().push(PaneInfo {
+            title: String::from("status-bar"),
+            is_plugin: true,
+            ..pane(7)
+        });
+        state.stars = star::StarGroups::new(vec![String::from("front"), String::from("back")]);
+        state.update_state();
+        state.stars.active_mut().toggle(PaneId::Terminal(3));
+        state.stars.active_mut().toggle(PaneId::Plugin(7));
+        // Starred only in a group that is not active.
+        state.stars.next_group();
+        state.stars.active_mut().toggle(PaneId::Terminal(1));
+        state.stars.active_mut().toggle(PaneId::Terminal(3));
+        state.stars.previous_group();
+        let pane_ids = state.pane_ids();
+        state.focus_history.visit(PaneId::Terminal(2), &pane_ids);
+        state.focus_history.visit(PaneId::Terminal(3), &pane_ids);
+
+        insta::assert_snapshot!(state.list_panes());
+    }
+
+    #[rstest]
+    #[case::clear_match("psql", config::DEFAULT_JUMP_SCORE_GAP, Some(PaneId::Terminal(1)))]
+    #[case::ambiguous("logs", config::DEFAULT_JUMP_SCORE_GAP, None)]
+    #[case::narrowed(
+        "logs worker",
+        config::DEFAULT_JUMP_SCORE_GAP,
+        Some(PaneId::Terminal(4))
+    )]
+    #[case::filter("id:2", config::DEFAULT_JUMP_SCORE_GAP, Some(PaneId::Terminal(2)))]
+    #[case::no_gap("logs", 0, Some(PaneId::Terminal(3)))]
+    #[case::no_match("emacs", 0, None)]
+    fn jump_target(
+        #[case] query: &str,
+        #[case] jump_score_gap: u32,
+        #[case] expected: Option<PaneId>,
+    ) {
+        let mut state = State {
+            tab_infos: vec![tab("Tab")],
+            pane_infos: HashMap::from([(
+                0,
+                ["psql prod", "vim", "logs api", "logs worker"]
+                    .iter()
+                    .zip(1..)
+                    .map(|(title, id)| PaneInfo {
+                        title: title.to_string(),
+                        ..pane(id)
+                    })
+                    .collect(),
+            )]),
+            config: config::Config {
+                jump_score_gap,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.update_state();
+
+        assert_eq!(state.jump_target(query), expected);
     }
 
-    fn panes_as_table(&mut self, width: usize) -> Table {
-        let star = "*";
-        let max_tab_col_length = 12;
+    #[rstest]
+    fn search_for_focus(marked_state: State) {
+        let state = marked_state;
 
-        // Calculate the width of tab name column.
-        let tab_name_width = min(
-            self.panes
-                .iter()
-                .map(|pane| pane.tab_name.len())
-                .max()
-                .unwrap_or(max_tab_col_length),
-            max_tab_col_length,
+        let got: Vec<PaneId> = state
+            .search(&state.panes, "pane 3")
+            .iter()
+            .map(|pane| pane.pane_id)
+            .collect();
+
+        assert_eq!(got.first(), Some(&PaneId::Terminal(3)));
+        assert!(state.search(&state.panes, "id:9").is_empty());
+    }
+
+    #[rstest]
+    fn start_rename(marked_state: State) {
+        let mut state = marked_state;
+
+        state.start_rename();
+        let input = state.input.take().unwrap();
+        assert_eq!(input.action, Action::RenamePanes(vec![PaneId::Terminal(1)]));
+        assert_eq!(input.editor.text(), "Pane 1");
+
+        state.marked = HashSet::from([PaneId::Terminal(2), PaneId::Terminal(3)]);
+        state.start_rename();
+        let input = state.input.take().unwrap();
+        assert_eq!(
+            input.action,
+            Action::RenamePanes(vec![PaneId::Terminal(2), PaneId::Terminal(3)])
         );
+        assert_eq!(input.editor.text(), "");
+    }
 
-        // Calculate the width of pane title column.
-        let pane_title_width = width - (star.len() + 1 + tab_name_width + 1 + 3);
+    #[rstest]
+    #[case::none(&[], None)]
+    #[case::one(&["invalid value \"x\" for layout"], Some("Configuration error: invalid value \"x\" for layout"))]
+    #[case::all(&["a", "b"], Some("Configuration errors: a; b"))]
+    fn report_configuration_errors(#[case] errors: &[&str], #[case] expected: Option<&str>) {
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
 
-        let mut matcher = Matcher::new(Config::DEFAULT.match_paths());
-        let search_result =
-            Pattern::parse(&self.search_key, CaseMatching::Ignore, Normalization::Smart)
-                .match_list(&self.panes, &mut matcher);
+        assert_eq!(configuration_error(&errors).as_deref(), expected);
+    }
 
-        self.display_panes = search_result
-            .iter()
-            .map(|(pane, _)| {
-                Pane::new(pane.tab_name.clone(), pane.pane_id, pane.pane_title.clone())
-            })
-            .collect();
+    #[test]
+    fn tab_index_counts_from_one() {
+        assert_eq!(tab_index(0), 1);
+        assert_eq!(tab_index(2), 3);
+    }
 
-        let mut table = Table::new().add_row(vec![
-            " ",
-            &format!("{:<width$}", "Tab", width = tab_name_width),
-            " ID",
-            &format!("{:<width$}", "Pane Title", width = pane_title_width),
-        ]);
+    #[rstest]
+    #[case::exact("api", Some(2))]
+    #[case::ignore_case("API", Some(2))]
+    #[case::contains("serv", Some(1))]
+    #[case::missing("docs", None)]
+    fn resolve_tab_name(#[case] name: &str, #[case] expected: Option<usize>) {
+        let tab_infos = vec![
+            TabInfo {
+                position: 0,
+                name: String::from("api-client"),
+                ..Default::default()
+            },
+            TabInfo {
+                position: 1,
+                name: String::from("server"),
+                ..Default::default()
+            },
+            TabInfo {
+                position: 2,
+                name: String::from("api"),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(resolve_tab(&tab_infos, name), expected);
+    }
 
-        for (i, pane) in self.display_panes.iter().enumerate() {
-            let pane_id = match pane.pane_id {
-                PaneId::Terminal(id) => id,
-                PaneId::Plugin(id) => id,
-            };
+    #[test]
+    fn tabs_as_table() {
+        let mut state = State {
+            tab_infos: vec![tab("editor"), active_tab("server"), tab("docs")],
+            pane_infos: HashMap::from([
+                (0, vec![pane(1), pane(2)]),
+                (1, vec![pane(3), pane(4), pane(5)]),
+            ]),
+            mode: Mode::Tabs,
+            search_key: editor::LineEditor::from("er"),
+            ..Default::default()
+        };
+        state.stars.active_mut().toggle(PaneId::Terminal(4));
+        state.update_state();
 
-            let mut star_column = Text::new(if self.stars.has(&pane.pane_id) {
-                star
-            } else {
-                " "
-            })
-            .color_range(0, ..);
-            let mut tab_name_column = Text::new(clip(&pane.tab_name, tab_name_width));
-            let mut pane_id_column = Text::new(format!("{:3}", pane_id));
-            let mut pane_title_column = Text::new(clip(&pane.pane_title, pane_title_width));
+        insta::assert_snapshot!(format!(
+            "\u{1b}Pztable;{}",
+            state.tabs_as_table(30, 20).serialize()
+        ));
 
-            if i == self.selected {
-                star_column = star_column.selected();
-                tab_name_column = tab_name_column.selected();
-                pane_id_column = pane_id_column.selected();
-                pane_title_column = pane_title_column.selected();
-            }
+        let got: Vec<&str> = state.display_tabs.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(got, ["editor", "server"]);
+        assert_eq!(state.display_tabs[1].pane_ids.len(), 3);
+    }
 
-            table = table.add_styled_row(vec![
-                star_column,
-                tab_name_column,
-                pane_id_column,
-                pane_title_column,
-            ]);
-        }
+    #[test]
+    fn switch_mode() {
+        let mut state = State {
+            tab_infos: vec![tab("Tab 1"), tab("Tab 2")],
+            pane_infos: HashMap::from([(0, vec![pane(1), pane(2), pane(3)])]),
+            ..Default::default()
+        };
+        state.update_state();
+        state.panes_as_table(30, 20);
+        state.select_last();
+        assert_eq!(state.selected, 2);
 
-        table
+        state.switch_mode(state.mode.next());
+        state.tabs_as_table(30, 20);
+        assert_eq!(state.mode, Mode::Tabs);
+        assert_eq!(state.selected, 0);
+        state.select_upward();
+        assert_eq!(state.selected, 1);
+
+        state.switch_mode(state.mode.next());
+        assert_eq!(state.mode, Mode::Sessions);
+        state.switch_mode(state.mode.next());
+        assert_eq!(state.mode, Mode::Panes);
+        assert_eq!(state.selected, 0);
     }
 
-    fn select_downward(&mut self) {
-        if !self.display_panes.is_empty() {
-            self.selected = (self.selected + 1) % self.display_panes.len();
-        }
+    #[fixture]
+    fn sessions() -> Vec<SessionInfo> {
+        vec![
+            SessionInfo {
+                name: String::from("web-shop"),
+                tabs: vec![
+                    tab("api"),
+                    TabInfo {
+                        position: 1,
+                        ..tab("docs")
+                    },
+                ],
+                panes: PaneManifest {
+                    panes: HashMap::from([
+                        (
+                            0,
+                            vec![
+                                PaneInfo {
+                                    title: String::from("cargo watch"),
+                                    ..pane(1)
+                                },
+                                // The plugin of this session.
+                                PaneInfo {
+                                    is_plugin: true,
+                                    ..pane(2)
+                                },
+                            ],
+                        ),
+                        (
+                            1,
+                            vec![PaneInfo {
+                                title: String::from("vim README.md"),
+                                ..pane(3)
+                            }],
+                        ),
+                    ]),
+                },
+                is_current_session: true,
+                ..Default::default()
+            },
+            SessionInfo {
+                name: String::from("dotfiles"),
+                tabs: vec![tab("main")],
+                panes: PaneManifest {
+                    panes: HashMap::from([(
+                        0,
+                        vec![PaneInfo {
+                            title: String::from("nvim init.lua"),
+                            ..pane(1)
+                        }],
+                    )]),
+                },
+                ..Default::default()
+            },
+        ]
     }
 
-    fn select_upward(&mut self) {
-        if !self.display_panes.is_empty() {
-            self.selected =
-                (self.selected + self.display_panes.len() - 1) % self.display_panes.len();
+    #[rstest]
+    fn next_global_star(sessions: Vec<SessionInfo>) {
+        let current = &sessions[0];
+        let mut state = State {
+            tab_infos: current.tabs.clone(),
+            pane_infos: current.panes.panes.clone(),
+            session_panes: session_panes(
+                &sessions,
+                &[(String::from("blog"), Duration::from_secs(60))],
+                Some(2),
+            ),
+            session_name: Some(current.name.clone()),
+            plugin_id: Some(2),
+            ..Default::default()
+        };
+        state.update_state();
+        state
+            .focus_history
+            .visit(PaneId::Terminal(1), &state.pane_ids());
+
+        let global_star = |session: &str, tab_name: &str, title: &str| global_star::GlobalStar {
+            session: session.to_string(),
+            key: star::PaneKey {
+                is_plugin: false,
+                tab_name: tab_name.to_string(),
+                title: title.to_string(),
+            },
+        };
+        for star in [
+            global_star("web-shop", "api", "cargo watch"),
+            // Neither running nor resurrectable.
+            global_star("gone", "main", "vim"),
+            global_star("dotfiles", "main", "nvim init.lua"),
+            global_star("blog", "main", "hugo serve"),
+        ] {
+            state.global_stars.set(star, true, 1);
         }
+
+        let next = state.next_global_star(true).unwrap();
+        assert_eq!(next.pane_title, "nvim init.lua");
+        assert_eq!(next.session.as_ref().unwrap().name, "dotfiles");
+
+        let previous = state.next_global_star(false).unwrap();
+        assert!(previous.session.as_ref().unwrap().is_resurrectable);
+        assert_eq!(previous.session.as_ref().unwrap().name, "blog");
+
+        // Stars of this session are focused without switching sessions.
+        state.global_stars = global_star::GlobalStars::default();
+        state
+            .global_stars
+            .set(global_star("web-shop", "docs", "vim README.md"), true, 1);
+        let next = state.next_global_star(true).unwrap();
+        assert_eq!(next.pane_id, PaneId::Terminal(3));
+        assert!(next.session.is_none());
     }
-}
 
-fn clip(string: &str, max_len: usize) -> String {
-    let ellipsis = "...";
+    #[test]
+    fn global_stars_follow_the_panes() {
+        let path = std::env::temp_dir().join(format!(
+            "zellij-pane-picker-{}-global-stars",
+            std::process::id()
+        ));
+        let mut state = State {
+            tab_infos: vec![tab("Tab")],
+            pane_infos: HashMap::from([(
+                0,
+                vec![
+                    PaneInfo {
+                        title: String::from("vim"),
+                        ..pane(1)
+                    },
+                    pane(2),
+                ],
+            )]),
+            stars: star::StarGroups::new(vec![String::from("front"), String::from("back")]),
+            global_stars_path: Some(path.clone()),
+            session_name: Some(String::from("web")),
+            ..Default::default()
+        };
+        state.update_state();
+        let titles = |state: &State| -> Vec<String> {
+            state
+                .global_stars
+                .stars()
+                .map(|star| star.key.title.clone())
+                .collect()
+        };
 
-    if string.len() > max_len {
-        if max_len >= ellipsis.len() {
-            format!("{}{}", &string[..(max_len - ellipsis.len())], ellipsis)
-        } else {
-            // return ellipsis to max_len
-            ellipsis.chars().take(max_len).collect::<String>()
-        }
-    } else {
-        string.to_string()
-    }
-}
+        // A pane stays starred while any group has it.
+        state.toggle_star(PaneId::Terminal(1));
+        state.stars.next_group();
+        state.toggle_star(PaneId::Terminal(1));
+        state.stars.previous_group();
+        state.toggle_star(PaneId::Terminal(1));
 
-#[cfg(not(test))]
-register_plugin!(State);
+        assert_eq!(titles(&state), ["vim"]);
 
-impl ZellijPlugin for State {
-    fn load(&mut self, configuration: BTreeMap<String, String>) {
-        self.keybinds = keybind::Keybinds::try_from(configuration).unwrap();
-        self.plugin_id = Some(get_plugin_ids().plugin_id);
+        // A renamed pane is starred under its new title.
+        state.pane_infos.get_mut(&0).unwrap()[0].title = String::from("nvim");
+        state.update_state();
 
-        request_permission(&[
-            PermissionType::ChangeApplicationState,
-            PermissionType::ReadApplicationState,
-            PermissionType::Reconfigure,
-        ]);
+        assert_eq!(titles(&state), ["nvim"]);
+        let saved = global_star::GlobalStars::decode(&persist::read(&path).unwrap().unwrap());
+        assert_eq!(saved, state.global_stars);
 
-        subscribe(&[
-            EventType::ModeUpdate,
-            EventType::Key,
-            EventType::PaneUpdate,
-            EventType::TabUpdate,
-        ]);
-    }
+        // A closed pane is unstarred.
+        state.pane_infos.get_mut(&0).unwrap().remove(0);
+        state.update_state();
 
-    fn update(&mut self, event: Event) -> bool {
-        match event {
-            Event::ModeUpdate(mode_info) => {
-                if let Some(base_mode) = mode_info.base_mode {
-                    if !self.bound_key {
-                        if let Some(plugin_id) = self.plugin_id {
-                            self.keybinds
-                                .bind_global_keys(base_mode, plugin_id, reconfigure);
-                        }
-                    }
-                }
-            }
-            Event::TabUpdate(tab_infos) => {
-                self.tab_infos = tab_infos;
-                self.update_state();
-            }
-            Event::PaneUpdate(PaneManifest { panes }) => {
-                self.pane_infos = panes;
-                self.update_state();
-            }
-            Event::Key(key) => {
-                if Some(key.clone()) == self.keybinds.plugin_select_down {
-                    self.select_downward();
-                } else if Some(key.clone()) == self.keybinds.plugin_select_up {
-                    self.select_upward()
-                } else if Some(key.clone()) == self.keybinds.plugin_navigate_to {
-                    focus_pane_with_id(self.display_panes[self.selected].pane_id, true);
-                    self.search_key.clear();
-                    hide_self();
-                } else if Some(key.clone()) == self.keybinds.plugin_hide {
-                    self.search_key.clear();
-                    hide_self();
-                } else if Some(key.clone()) == self.keybinds.plugin_toggle_star {
-                    let selected_pane_id = self.display_panes[self.selected].pane_id;
-                    self.stars.toggle(selected_pane_id);
-                } else if let BareKey::Char(c) = key.bare_key {
-                    if key.has_no_modifiers() {
-                        self.search_key.push(c);
-                    }
-                } else if let BareKey::Backspace = key.bare_key {
-                    self.search_key.pop();
-                }
-            }
-            _ => {}
-        }
-        true
+        assert!(titles(&state).is_empty());
+
+        std::fs::remove_file(path).unwrap();
     }
 
-    fn pipe(&mut self, pipe_message: PipeMessage) -> bool {
-        if pipe_message.source == PipeSource::Keybind && pipe_message.is_private {
-            if pipe_message.name == keybind::LIST_PANES {
-                show_self(true);
-            } else if pipe_message.name == keybind::NAVIGATE_BACK {
-                if let Some(id) = self.previous_focus {
-                    focus_pane_with_id(id, true);
-                }
-            } else if pipe_message.name == keybind::TOGGLE_STAR {
-                if let Some(pane_id) = self.current_focus {
-                    self.stars.toggle(pane_id);
-                }
-            } else if pipe_message.name == keybind::NEXT_STAR {
-                if let Some(pane_id) = self.current_focus {
-                    if let Some(id) = self.stars.next(&pane_id) {
-                        focus_pane_with_id(*id, true);
-                    }
-                }
-            } else if pipe_message.name == keybind::PREV_STAR {
-                if let Some(pane_id) = self.current_focus {
-                    if let Some(id) = self.stars.previous(&pane_id) {
-                        focus_pane_with_id(*id, true);
-                    }
-                }
-            }
-            return true;
-        }
-        false
+    #[rstest]
+    #[case::output(Ok(String::from("terminal_1\n")), false, Some("terminal_1\n"))]
+    #[case::error(
+        Err(command::CommandError::NoMatch(String::from("vim"))),
+        false,
+        Some("error: no pane matches \"vim\"\n")
+    )]
+    #[case::unknown_broadcast(
+        Err(command::CommandError::Unknown(String::from("other"))),
+        false,
+        None
+    )]
+    #[case::unknown_to_this_plugin(
+        Err(command::CommandError::Unknown(String::from("fcous"))),
+        true,
+        Some("error: unknown command \"fcous\"\n")
+    )]
+    fn reply_to_pipe(
+        #[case] result: Result<String, command::CommandError>,
+        #[case] is_private: bool,
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(command_output(result, is_private).as_deref(), expected);
     }
 
-    fn render(&mut self, rows: usize, cols: usize) {
-        print_text_with_coordinates(
-            Text::new(format!("[SEARCH] {}", self.search_key))
-                .color_range(1, 0..=8)
-                .color_range(3, 9..),
-            1,
-            1,
-            Some(cols - 1),
-            Some(1),
-        );
+    #[rstest]
+    fn list_session_panes(sessions: Vec<SessionInfo>) {
+        let resurrectable = vec![(String::from("blog"), Duration::from_secs(60))];
 
-        let nested_list = self.panes_as_table(cols - 4);
-        print_table_with_coordinates(nested_list, 1, 3, Some(cols - 1), Some(rows - 2));
-    }
-}
+        let panes = session_panes(&sessions, &resurrectable, Some(2));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rstest::*;
+        let got: Vec<(&str, &str, bool)> = panes
+            .iter()
+            .map(|pane| {
+                let session = pane.session.as_ref().unwrap();
+                (
+                    session.name.as_str(),
+                    pane.pane_title.as_str(),
+                    session.is_current,
+                )
+            })
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("web-shop", "cargo watch", true),
+                ("web-shop", "vim README.md", true),
+                ("dotfiles", "nvim init.lua", false),
+                ("blog", "(resurrectable)", false),
+            ]
+        );
+        assert!(panes[3].session.as_ref().unwrap().is_resurrectable);
+        assert_eq!(panes[2].search_string, "dotfiles  main  nvim init.lua");
+        assert_eq!(panes[2].title_start(), 16);
+    }
 
-    #[test]
-    fn panes_as_table() {
+    #[rstest]
+    fn sessions_as_table(sessions: Vec<SessionInfo>) {
         let mut state = State {
-            tab_infos: vec![
-                TabInfo {
-                    name: String::from("Tab 1"),
-                    ..Default::default()
-                },
-                TabInfo {
-                    name: String::from("Tab 2"),
-                    ..Default::default()
-                },
-            ],
-            pane_infos: HashMap::from([
-                (
-                    // This will be the last because it is the last tab.
-                    1,
-                    vec![PaneInfo {
-                        id: 55,
-                        title: String::from("Pane 55"),
-                        is_selectable: true,
-                        ..Default::default()
-                    }],
-                ),
-                (
-                    0,
-                    vec![
-                        PaneInfo {
-                            id: 1,
-                            title: String::from("Pane 1"),
-                            is_selectable: true,
-                            ..Default::default()
-                        },
-                        PaneInfo {
-                            id: 2,
-                            title: String::from("Pane 2"),
-                            is_selectable: true,
-                            ..Default::default()
-                        },
-                        // Hidden because is not selectable
-                        PaneInfo {
-                            id: 3,
-                            title: String::from("Pane 3 (not selectable)"),
-                            ..Default::default()
-                        },
-                        // Hidden because is suppressed
-                        PaneInfo {
-                            id: 4,
-                            title: String::from("Pane 4 (suppressed)"),
-                            is_selectable: true,
-                            is_suppressed: true,
-                            ..Default::default()
-                        },
-                        // Hidden because is the plugin
-                        PaneInfo {
-                            id: 4,
-                            title: String::from("Pane 4 (suppressed)"),
-                            is_selectable: true,
-                            is_plugin: true,
-                            ..Default::default()
-                        },
-                    ],
-                ),
-                (
-                    // The following tab does not exist.
-                    2,
-                    vec![PaneInfo {
-                        id: 99,
-                        title: String::from("Pane 99 on non existing tab"),
-                        is_selectable: true,
-                        ..Default::default()
-                    }],
-                ),
-            ]),
-            selected: 1,
-            plugin_id: Some(4),
+            session_panes: session_panes(
+                &sessions,
+                &[(String::from("blog"), Duration::from_secs(60))],
+                Some(2),
+            ),
+            mode: Mode::Sessions,
+            search_key: editor::LineEditor::from("i"),
             ..Default::default()
         };
-
-        state.stars.toggle(PaneId::Terminal(2));
-        state.update_state();
+        // Stars of this session do not apply to the panes of other sessions.
+        state.stars.active_mut().toggle(PaneId::Terminal(1));
 
         insta::assert_snapshot!(format!(
             "\u{1b}Pztable;{}",
-            state.panes_as_table(20).serialize()
+            state.panes_as_table(40, 20).serialize()
         ));
+
+        state.search_key = editor::LineEditor::from("session:dot vim");
+        state.panes_as_table(40, 20);
+        let got: Vec<&str> = state
+            .display_panes
+            .iter()
+            .map(|p| p.pane_title.as_str())
+            .collect();
+        assert_eq!(got, ["nvim init.lua"]);
     }
 
     #[test]
@@ -520,6 +3680,32 @@ mod tests {
         assert_eq!(state.selected, 1);
     }
 
+    /// Return the width of every column of table, the widest of its cells,
+    /// read back from the serialized table.
+    fn column_widths(table: &Table) -> Vec<usize> {
+        let serialized = table.serialize();
+        let mut fields = serialized.trim_end_matches("\u{1b}\\").split(';');
+        let columns: usize = fields.next().unwrap().parse().unwrap();
+        fields.next();
+
+        let mut widths = vec![0; columns];
+        for (i, cell) in fields.enumerate() {
+            let bytes = cell
+                .rsplit('$')
+                .next()
+                .unwrap()
+                .trim_start_matches(['x', 'z']);
+            let bytes: Vec<u8> = bytes
+                .split(',')
+                .filter(|byte| !byte.is_empty())
+                .map(|byte| byte.parse().unwrap())
+                .collect();
+            let width = text::width(&String::from_utf8(bytes).unwrap());
+            widths[i % columns] = widths[i % columns].max(width);
+        }
+        widths
+    }
+
     #[fixture]
     fn tab(#[default("Tab")] name: &str) -> TabInfo {
         TabInfo {
@@ -577,6 +3763,15 @@ mod tests {
         PaneId::Terminal(11)
     }
 
+    #[fixture]
+    fn focus_history(current_focus: PaneId, previous_focus: PaneId) -> focus::FocusHistory {
+        let mut focus_history = focus::FocusHistory::default();
+        let pane_ids = HashSet::from([current_focus, previous_focus]);
+        focus_history.visit(previous_focus, &pane_ids);
+        focus_history.visit(current_focus, &pane_ids);
+        focus_history
+    }
+
     #[rstest]
     #[case::no_panes_in_tab(vec![ active_tab("Tab") ], HashMap::from([(1, vec![focus_pane(1)])]))]
     #[case::no_tabs(vec![], HashMap::from([(1, vec![focus_pane(1)])]))]
@@ -587,19 +3782,19 @@ mod tests {
         #[case] pane_infos: HashMap<usize, Vec<PaneInfo>>,
         current_focus: PaneId,
         previous_focus: PaneId,
+        focus_history: focus::FocusHistory,
     ) {
         let mut state = State {
             tab_infos,
             pane_infos,
-            current_focus: Some(current_focus),
-            previous_focus: Some(previous_focus),
+            focus_history,
             ..Default::default()
         };
 
         state.update_state();
 
-        assert_eq!(state.current_focus, Some(current_focus));
-        assert_eq!(state.previous_focus, Some(previous_focus));
+        assert_eq!(state.focus_history.current(), Some(current_focus));
+        assert_eq!(state.focus_history.previous(), Some(previous_focus));
     }
 
     #[rstest]
@@ -611,28 +3806,18 @@ mod tests {
         #[case] pane_infos: HashMap<usize, Vec<PaneInfo>>,
         #[case] new_current_focus: PaneId,
         #[case] new_previous_focus: Option<PaneId>,
+        focus_history: focus::FocusHistory,
     ) {
         let mut state = State {
             tab_infos,
             pane_infos,
-            current_focus: Some(PaneId::Terminal(10)),
-            previous_focus: Some(PaneId::Terminal(11)),
+            focus_history,
             ..Default::default()
         };
 
         state.update_state();
 
-        assert_eq!(state.current_focus, Some(new_current_focus));
-        assert_eq!(state.previous_focus, new_previous_focus);
-    }
-
-    #[rstest]
-    #[case("Lorem ipsum dolor sit amet", 100, "Lorem ipsum dolor sit amet".to_string())]
-    #[case("Lorem ipsum dolor sit amet", 5, "Lo...".to_string())]
-    #[case("Lorem ipsum dolor sit amet", 2, "..".to_string())]
-    #[case("Lorem ipsum dolor sit amet", 0, "".to_string())]
-    fn clip_text(#[case] text: &str, #[case] max_len: usize, #[case] expected: String) {
-        let got = clip(text, max_len);
-        assert_eq!(expected, got);
+        assert_eq!(state.focus_history.current(), Some(new_current_focus));
+        assert_eq!(state.focus_history.previous(), new_previous_focus);
     }
 }
@@ -0,0 +1,125 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The directory Zellij maps to the plugin's persistent data folder.
+pub const DATA_DIR: &str = "/data";
+
+/// A failure to read or write a file of the data folder.
+#[derive(Debug, Error)]
+pub enum PersistError {
+    #[error("failed to read {}: {source}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("failed to write {}: {source}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+}
+
+/// Return the path of a file inside the plugin's data folder.
+pub fn data_file(name: &str) -> PathBuf {
+    Path::new(DATA_DIR).join(name)
+}
+
+/// Return the path of a file of one session inside the plugin's data folder.
+/// The data folder is shared by all sessions, so the session name becomes
+/// a directory, with bytes other than ASCII letters, digits, '-' and '_' percent-encoded.
+pub fn session_file(session_name: &str, name: &str) -> PathBuf {
+    let mut dir = String::new();
+    for byte in session_name.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            dir.push(char::from(byte));
+        } else {
+            dir.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    Path::new(DATA_DIR).join("sessions").join(dir).join(name)
+}
+
+/// Read the whole file into a string.
+/// A missing file is not an error and yields None.
+pub fn read(path: &Path) -> Result<Option<String>, PersistError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(source) => Err(PersistError::Read {
+            path: path.to_path_buf(),
+            source,
+        }),
+    }
+}
+
+/// Write contents into a temporary file next to path and rename it into place
+/// so that readers never observe a half-written file.
+pub fn write(path: &Path, contents: &str) -> Result<(), PersistError> {
+    write_file(path, contents).map_err(|source| PersistError::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_and_read() {
+        let dir = std::env::temp_dir().join(format!("zellij-pane-picker-{}", std::process::id()));
+        let path = dir.join("nested").join("file");
+
+        assert_eq!(read(&path).unwrap(), None);
+
+        write(&path, "first").unwrap();
+        write(&path, "second").unwrap();
+        assert_eq!(read(&path).unwrap(), Some(String::from("second")));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn report_failures() {
+        let dir =
+            std::env::temp_dir().join(format!("zellij-pane-picker-{}-dir", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // A directory can be neither read as a file nor replaced by one.
+        assert!(matches!(read(&dir), Err(PersistError::Read { .. })));
+        let err = write(&dir, "contents").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with(&format!("failed to write {}: ", dir.display())));
+
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(format!("{}.tmp", dir.display())).unwrap();
+    }
+
+    #[test]
+    fn data_file_is_in_data_dir() {
+        assert_eq!(data_file("stars"), PathBuf::from("/data/stars"));
+    }
+
+    #[test]
+    fn session_file_is_per_session() {
+        assert_eq!(
+            session_file("bright-owl_2", "stars"),
+            PathBuf::from("/data/sessions/bright-owl_2/stars")
+        );
+        assert_eq!(
+            session_file("../a b/é", "stars"),
+            PathBuf::from("/data/sessions/%2E%2E%2Fa%20b%2F%C3%A9/stars")
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use zellij_tile::prelude::PaneInfo;
+
+/// The plugin pane needs this many columns to show the preview next to the list.
+const SIDE_BY_SIDE_MIN_COLS: usize = 100;
+/// The plugin pane needs this many rows to show the preview below the list.
+const BELOW_MIN_ROWS: usize = 20;
+
+/// A rectangle of cells on the plugin pane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub cols: usize,
+    pub rows: usize,
+}
+
+/// Where the list of panes and the preview of the selected pane are drawn.
+#[derive(Debug, PartialEq)]
+pub struct Layout {
+    pub list: Rect,
+    pub preview: Option<Rect>,
+}
+
+impl Layout {
+    /// Split area between the list and the preview.
+    ///
+    /// Wide areas show the preview on the right, tall areas below the list.
+    /// Areas that are too small for both show the list only.
+    pub fn new(area: Rect, show_preview: bool) -> Self {
+        if !show_preview {
+            return Self {
+                list: area,
+                preview: None,
+            };
+        }
+
+        if area.cols >= SIDE_BY_SIDE_MIN_COLS {
+            let list_cols = area.cols * 3 / 5;
+            Self {
+                list: Rect {
+                    cols: list_cols,
+                    ..area
+                },
+                preview: Some(Rect {
+                    x: area.x + list_cols + 1,
+                    cols: area.cols - list_cols - 1,
+                    ..area
+                }),
+            }
+        } else if area.rows >= BELOW_MIN_ROWS {
+            let list_rows = area.rows / 2;
+            Self {
+                list: Rect {
+                    rows: list_rows,
+                    ..area
+                },
+                preview: Some(Rect {
+                    y: area.y + list_rows + 1,
+                    rows: area.rows - list_rows - 1,
+                    ..area
+                }),
+            }
+        } else {
+            Self {
+                list: area,
+                preview: None,
+            }
+        }
+    }
+}
+
+/// Describe the pane in lines of text, the title first.
+///
+/// The plugin API does not give access to the contents of other panes,
+/// so the preview shows what Zellij reports about the pane instead.
+pub fn lines(tab_name: &str, pane_info: &PaneInfo) -> Vec<String> {
+    let mut lines = vec![pane_info.title.clone(), format!("Tab: {}", tab_name)];
+
+    if let Some(command) = &pane_info.terminal_command {
+        lines.push(format!("Command: {}", command));
+    }
+    if let Some(url) = &pane_info.plugin_url {
+        lines.push(format!("Plugin: {}", url));
+    }
+
+    lines.push(format!(
+        "Size: {}x{} at {},{}",
+        pane_info.pane_columns, pane_info.pane_rows, pane_info.pane_x, pane_info.pane_y
+    ));
+
+    let mut states = Vec::new();
+    if pane_info.is_floating {
+        states.push(String::from("floating"));
+    }
+    if pane_info.is_fullscreen {
+        states.push(String::from("fullscreen"));
+    }
+    if pane_info.exited {
+        states.push(match pane_info.exit_status {
+            Some(status) => format!("exited with status {}", status),
+            None => String::from("exited"),
+        });
+    }
+    if !states.is_empty() {
+        lines.push(format!("State: {}", states.join(", ")));
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    const AREA: Rect = Rect {
+        x: 1,
+        y: 3,
+        cols: 0,
+        rows: 0,
+    };
+
+    fn rect(x: usize, y: usize, cols: usize, rows: usize) -> Rect {
+        Rect { x, y, cols, rows }
+    }
+
+    #[rstest]
+    #[case::hidden(120, 40, false, rect(1, 3, 120, 40), None)]
+    #[case::side_by_side(120, 40, true, rect(1, 3, 72, 40), Some(rect(74, 3, 47, 40)))]
+    #[case::below(80, 40, true, rect(1, 3, 80, 20), Some(rect(1, 24, 80, 19)))]
+    #[case::too_small(80, 10, true, rect(1, 3, 80, 10), None)]
+    fn layout(
+        #[case] cols: usize,
+        #[case] rows: usize,
+        #[case] show_preview: bool,
+        #[case] list: Rect,
+        #[case] preview: Option<Rect>,
+    ) {
+        let area = Rect { cols, rows, ..AREA };
+
+        assert_eq!(Layout::new(area, show_preview), Layout { list, preview });
+    }
+
+    #[test]
+    fn describe_command_pane() {
+        let pane_info = PaneInfo {
+            title: String::from("tests"),
+            terminal_command: Some(String::from("cargo watch -x test")),
+            pane_x: 10,
+            pane_y: 2,
+            pane_columns: 80,
+            pane_rows: 24,
+            is_floating: true,
+            exited: true,
+            exit_status: Some(101),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            lines("api", &pane_info),
+            vec![
+                "tests",
+                "Tab: api",
+                "Command: cargo watch -x test",
+                "Size: 80x24 at 10,2",
+                "State: floating, exited with status 101",
+            ]
+        );
+    }
+
+    #[test]
+    fn describe_plugin_pane() {
+        let pane_info = PaneInfo {
+            title: String::from("status-bar"),
+            is_plugin: true,
+            plugin_url: Some(String::from("zellij:status-bar")),
+            pane_columns: 120,
+            pane_rows: 1,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            lines("editor", &pane_info),
+            vec![
+                "status-bar",
+                "Tab: editor",
+                "Plugin: zellij:status-bar",
+                "Size: 120x1 at 0,0",
+            ]
+        );
+    }
+}
@@ -0,0 +1,202 @@
+/// A condition on a single field of a pane.
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Tab(String),
+    Title(String),
+    Id(u32),
+    Cmd(String),
+    Session(String),
+    Is(Flag),
+}
+
+/// A property of a pane that can be queried with `is:`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Flag {
+    Starred,
+    Plugin,
+    Terminal,
+    Floating,
+}
+
+/// The fields of a pane that filters are checked against.
+pub struct Fields<'a> {
+    pub tab_name: &'a str,
+    pub title: &'a str,
+    pub id: u32,
+    pub command: Option<&'a str>,
+    pub session: Option<&'a str>,
+    pub is_plugin: bool,
+    pub is_floating: bool,
+    pub is_starred: bool,
+}
+
+/// A search query typed into the search prompt.
+///
+/// Terms of the form `field:value` restrict the panes to those where the field
+/// matches, e.g., `tab:api title:logs`, `id:12`, `cmd:cargo`, `session:web`, or `is:starred`.
+/// All other terms make up the free text that is matched fuzzily.
+#[derive(Debug, Default, PartialEq)]
+pub struct Query {
+    text: String,
+    filters: Vec<Filter>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut text = Vec::new();
+        let mut filters = Vec::new();
+
+        for term in input.split_whitespace() {
+            match parse_filter(term) {
+                Some(filter) => filters.push(filter),
+                None => text.push(term),
+            }
+        }
+
+        Self {
+            text: text.join(" "),
+            filters,
+        }
+    }
+
+    /// Return the free text of the query for fuzzy matching.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Check if the pane satisfies all filters of the query.
+    pub fn matches(&self, fields: &Fields) -> bool {
+        self.filters.iter().all(|filter| match filter {
+            Filter::Tab(value) => contains(fields.tab_name, value),
+            Filter::Title(value) => contains(fields.title, value),
+            Filter::Id(id) => fields.id == *id,
+            Filter::Cmd(value) => fields.command.is_some_and(|c| contains(c, value)),
+            Filter::Session(value) => fields.session.is_some_and(|s| contains(s, value)),
+            Filter::Is(Flag::Starred) => fields.is_starred,
+            Filter::Is(Flag::Plugin) => fields.is_plugin,
+            Filter::Is(Flag::Terminal) => !fields.is_plugin,
+            Filter::Is(Flag::Floating) => fields.is_floating,
+        })
+    }
+}
+
+/// Parse a `field:value` term.
+/// Terms with an unknown field or an invalid value are not filters.
+fn parse_filter(term: &str) -> Option<Filter> {
+    let (field, value) = term.split_once(':')?;
+    if value.is_empty() {
+        return None;
+    }
+
+    let value_lowercase = value.to_lowercase();
+
+    match field {
+        "tab" => Some(Filter::Tab(value_lowercase)),
+        "title" => Some(Filter::Title(value_lowercase)),
+        "id" => value.parse().ok().map(Filter::Id),
+        "cmd" => Some(Filter::Cmd(value_lowercase)),
+        "session" => Some(Filter::Session(value_lowercase)),
+        "is" => match value_lowercase.as_str() {
+            "starred" => Some(Filter::Is(Flag::Starred)),
+            "plugin" => Some(Filter::Is(Flag::Plugin)),
+            "terminal" => Some(Filter::Is(Flag::Terminal)),
+            "floating" => Some(Filter::Is(Flag::Floating)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Case-insensitive substring check, needle must already be in lowercase.
+fn contains(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::empty("", "", vec![])]
+    #[case::text_only("api  logs", "api logs", vec![])]
+    #[case::fields(
+        "tab:API title:Logs id:12 cmd:cargo session:Web",
+        "",
+        vec![
+            Filter::Tab(String::from("api")),
+            Filter::Title(String::from("logs")),
+            Filter::Id(12),
+            Filter::Cmd(String::from("cargo")),
+            Filter::Session(String::from("web")),
+        ]
+    )]
+    #[case::flags(
+        "is:starred is:Plugin is:terminal is:floating",
+        "",
+        vec![
+            Filter::Is(Flag::Starred),
+            Filter::Is(Flag::Plugin),
+            Filter::Is(Flag::Terminal),
+            Filter::Is(Flag::Floating),
+        ]
+    )]
+    #[case::mixed("vim tab:api src", "vim src", vec![Filter::Tab(String::from("api"))])]
+    #[case::not_filters(
+        "id:x is:unknown tab: http://host :value",
+        "id:x is:unknown tab: http://host :value",
+        vec![]
+    )]
+    fn parse(#[case] input: &str, #[case] text: &str, #[case] filters: Vec<Filter>) {
+        assert_eq!(
+            Query::parse(input),
+            Query {
+                text: text.to_string(),
+                filters
+            }
+        );
+    }
+
+    #[fixture]
+    fn fields() -> Fields<'static> {
+        Fields {
+            tab_name: "API server",
+            title: "tail -f logs",
+            id: 12,
+            command: Some("cargo watch"),
+            session: Some("web-shop"),
+            is_plugin: false,
+            is_floating: true,
+            is_starred: false,
+        }
+    }
+
+    #[rstest]
+    #[case("", true)]
+    #[case("free text is ignored", true)]
+    #[case("tab:api", true)]
+    #[case("tab:api title:LOGS", true)]
+    #[case("tab:api title:vim", false)]
+    #[case("id:12", true)]
+    #[case("id:1", false)]
+    #[case("cmd:watch", true)]
+    #[case("cmd:vim", false)]
+    #[case("session:shop", true)]
+    #[case("session:docs", false)]
+    #[case("is:floating is:terminal", true)]
+    #[case("is:plugin", false)]
+    #[case("is:starred", false)]
+    fn matches(fields: Fields, #[case] input: &str, #[case] expected: bool) {
+        assert_eq!(Query::parse(input).matches(&fields), expected);
+    }
+
+    #[test]
+    fn cmd_without_command() {
+        let fields = Fields {
+            command: None,
+            ..fields()
+        };
+
+        assert!(!Query::parse("cmd:cargo").matches(&fields));
+    }
+}
@@ -1,7 +1,43 @@
+use indexmap::IndexMap;
 use indexmap::IndexSet;
 use std::collections::HashSet;
 use zellij_tile::prelude::PaneId;
 
+/// Header of the on-disk format written by [encode].
+const FORMAT_HEADER: &str = "# zellij-pane-picker stars v2";
+/// Header of the on-disk format before star groups, whose lines have no group.
+const FORMAT_HEADER_V1: &str = "# zellij-pane-picker stars v1";
+
+pub const KIND_TERMINAL: &str = "terminal";
+pub const KIND_PLUGIN: &str = "plugin";
+
+/// The group used when no group is configured.
+pub const DEFAULT_GROUP: &str = "default";
+
+/// How long entries loaded from disk wait for their panes, in milliseconds.
+/// Entries that are still unmatched afterwards are no longer matched, but saved again.
+const PENDING_MILLIS: u64 = 60 * 1000;
+
+/// The number of unmatched entries of configured groups that are saved again.
+/// The oldest are dropped beyond it so that the file does not grow forever.
+const KEPT_LIMIT: usize = 100;
+
+/// A description of a pane that stays stable across plugin reloads
+/// and session resurrection, unlike the numeric PaneId.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaneKey {
+    pub is_plugin: bool,
+    pub tab_name: String,
+    pub title: String,
+}
+
+/// A starred pane as it is persisted on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarEntry {
+    pub group: String,
+    pub key: PaneKey,
+}
+
 /// A collection of pane IDs that are starred.
 #[derive(Default)]
 pub struct Star {
@@ -29,6 +65,36 @@ impl Star {
         self.pane_ids.contains(pane_id)
     }
 
+    /// Return the pane_id at the given slot (zero-based position in the list).
+    pub fn get(&self, slot: usize) -> Option<&PaneId> {
+        self.pane_ids.get_index(slot)
+    }
+
+    /// Return the slot (zero-based position in the list) of pane_id.
+    pub fn slot_of(&self, pane_id: &PaneId) -> Option<usize> {
+        self.pane_ids.get_index_of(pane_id)
+    }
+
+    /// Move pane_id one slot towards the start of the list.
+    /// Nothing happens if it is already the first or not starred.
+    pub fn move_up(&mut self, pane_id: &PaneId) {
+        if let Some(index) = self.pane_ids.get_index_of(pane_id) {
+            if index > 0 {
+                self.pane_ids.swap_indices(index, index - 1);
+            }
+        }
+    }
+
+    /// Move pane_id one slot towards the end of the list.
+    /// Nothing happens if it is already the last or not starred.
+    pub fn move_down(&mut self, pane_id: &PaneId) {
+        if let Some(index) = self.pane_ids.get_index_of(pane_id) {
+            if index + 1 < self.pane_ids.len() {
+                self.pane_ids.swap_indices(index, index + 1);
+            }
+        }
+    }
+
     /// Add pane_id if it is not yet added
     /// and remove if it is already in the list.
     pub fn toggle(&mut self, pane_id: PaneId) {
@@ -82,6 +148,284 @@ impl Star {
     }
 }
 
+/// Named groups of starred panes, each with its own ordering.
+/// Starring and cycling operate on the active group.
+pub struct StarGroups {
+    groups: IndexMap<String, Star>,
+    active: usize,
+
+    // Entries loaded from disk that have not been matched to a pane yet,
+    // and the time in milliseconds after which they are no longer matched.
+    pending: Vec<StarEntry>,
+    pending_until: u64,
+    // Entries that are not matched but saved again: those of groups that are not
+    // configured, e.g., while the configuration is invalid, and those whose pane
+    // did not show up within PENDING_MILLIS.
+    kept: Vec<StarEntry>,
+}
+
+impl Default for StarGroups {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl StarGroups {
+    /// Create empty groups with the given names.
+    /// Without any name, a single group called DEFAULT_GROUP is created.
+    pub fn new(names: Vec<String>) -> Self {
+        let mut groups: IndexMap<String, Star> = names
+            .into_iter()
+            .map(|name| (name, Star::default()))
+            .collect();
+
+        if groups.is_empty() {
+            groups.insert(DEFAULT_GROUP.to_string(), Star::default());
+        }
+
+        Self {
+            groups,
+            active: 0,
+            pending: Vec::new(),
+            pending_until: 0,
+            kept: Vec::new(),
+        }
+    }
+
+    /// Return the number of groups.
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Return the name of the first group, which is active at startup.
+    pub fn first_name(&self) -> &str {
+        self.groups.get_index(0).map_or("", |(name, _)| name)
+    }
+
+    /// Return the name of the active group.
+    pub fn active_name(&self) -> &str {
+        self.groups
+            .get_index(self.active)
+            .map_or("", |(name, _)| name)
+    }
+
+    /// Return the stars of the active group.
+    pub fn active(&self) -> &Star {
+        &self.groups[self.active]
+    }
+
+    /// Return the stars of the active group for modification.
+    pub fn active_mut(&mut self) -> &mut Star {
+        &mut self.groups[self.active]
+    }
+
+    /// Activate the group after the active one, wrapping around at the end.
+    pub fn next_group(&mut self) {
+        self.active = (self.active + 1) % self.groups.len();
+    }
+
+    /// Activate the group before the active one, wrapping around at the start.
+    pub fn previous_group(&mut self) {
+        self.active = (self.active + self.groups.len() - 1) % self.groups.len();
+    }
+
+    /// Return the names of the groups that pane_id is starred in.
+    pub fn groups_of(&self, pane_id: &PaneId) -> Vec<&str> {
+        self.groups
+            .iter()
+            .filter(|(_, star)| star.has(pane_id))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Remove any pane_id that is not in pane_ids from every group.
+    pub fn sync(&mut self, pane_ids: &HashSet<PaneId>) {
+        for star in self.groups.values_mut() {
+            star.sync(pane_ids);
+        }
+    }
+
+    /// Check if an entry for key is waiting to be matched against a pane.
+    pub fn is_pending(&self, key: &PaneKey) -> bool {
+        self.pending.iter().any(|entry| entry.key == *key)
+    }
+
+    /// Replace the entries that are waiting to be matched against panes,
+    /// loaded at the time now in milliseconds.
+    /// Entries of groups that are not configured are only kept to be saved again.
+    pub fn set_pending(&mut self, entries: Vec<StarEntry>, now: u64) {
+        let (pending, kept) = entries
+            .into_iter()
+            .partition(|entry| self.groups.contains_key(&entry.group));
+        self.pending = pending;
+        self.kept = kept;
+        self.pending_until = now + PENDING_MILLIS;
+    }
+
+    /// Star the panes that match a pending entry, in the order of the entries.
+    /// Entries without a matching pane are kept pending because the pane might show up
+    /// in a later update, e.g., while a session is being resurrected. After
+    /// PENDING_MILLIS have passed since they were loaded, they are only saved again.
+    pub fn restore(&mut self, candidates: &[(PaneId, PaneKey)], now: u64) {
+        let pending = std::mem::take(&mut self.pending);
+
+        for entry in pending {
+            let Some(star) = self.groups.get_mut(&entry.group) else {
+                self.kept.push(entry);
+                continue;
+            };
+
+            let found = candidates
+                .iter()
+                .find(|(id, key)| *key == entry.key && !star.has(id));
+
+            match found {
+                Some((id, _)) => star.add(*id),
+                None => self.pending.push(entry),
+            }
+        }
+
+        if now >= self.pending_until && !self.pending.is_empty() {
+            self.kept.append(&mut self.pending);
+
+            let groups = &self.groups;
+            let known = self
+                .kept
+                .iter()
+                .filter(|entry| groups.contains_key(&entry.group))
+                .count();
+            let mut excess = known.saturating_sub(KEPT_LIMIT);
+            self.kept.retain(|entry| {
+                let drop = excess > 0 && groups.contains_key(&entry.group);
+                if drop {
+                    excess -= 1;
+                }
+                !drop
+            });
+        }
+    }
+
+    /// Return the entries to persist: the starred panes described by lookup
+    /// group by group, followed by the pending and the kept entries.
+    pub fn entries<F>(&self, lookup: F) -> Vec<StarEntry>
+    where
+        F: Fn(&PaneId) -> Option<PaneKey>,
+    {
+        self.groups
+            .iter()
+            .flat_map(|(group, star)| {
+                star.pane_ids
+                    .iter()
+                    .filter_map(&lookup)
+                    .map(move |key| StarEntry {
+                        group: group.clone(),
+                        key,
+                    })
+            })
+            .chain(self.pending.iter().cloned())
+            .chain(self.kept.iter().cloned())
+            .collect()
+    }
+}
+
+/// Serialize entries into the on-disk format:
+///
+/// ```text
+/// # zellij-pane-picker stars v2
+/// default<TAB>terminal<TAB>Tab 1<TAB>vim
+/// ops<TAB>plugin<TAB>Tab 2<TAB>filepicker
+/// ```
+///
+/// Each line holds the group, the pane kind, the tab name, and the pane title
+/// separated by tabs. Backslashes, tabs, and newlines inside the names are escaped as
+/// `\\`, `\t`, and `\n`.
+pub fn encode(entries: &[StarEntry]) -> String {
+    let mut out = String::from(FORMAT_HEADER);
+    out.push('\n');
+
+    for entry in entries {
+        let kind = if entry.key.is_plugin {
+            KIND_PLUGIN
+        } else {
+            KIND_TERMINAL
+        };
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            escape(&entry.group),
+            kind,
+            escape(&entry.key.tab_name),
+            escape(&entry.key.title)
+        ));
+    }
+
+    out
+}
+
+/// Parse the on-disk format written by [encode].
+/// Files of the v1 format, which has no group, are loaded into v1_group.
+/// Blank lines, comments, and malformed lines are skipped.
+pub fn decode(contents: &str, v1_group: &str) -> Vec<StarEntry> {
+    let v1 = contents.lines().next() == Some(FORMAT_HEADER_V1);
+
+    contents
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let (group, kind, tab_name, title) = match fields[..] {
+                [kind, tab_name, title] if v1 => (v1_group, kind, tab_name, title),
+                [group, kind, tab_name, title] if !v1 => (group, kind, tab_name, title),
+                _ => return None,
+            };
+
+            let is_plugin = match kind {
+                KIND_TERMINAL => false,
+                KIND_PLUGIN => true,
+                _ => return None,
+            };
+
+            Some(StarEntry {
+                group: unescape(group),
+                key: PaneKey {
+                    is_plugin,
+                    tab_name: unescape(tab_name),
+                    title: unescape(title),
+                },
+            })
+        })
+        .collect()
+}
+
+/// Escape the separators of the on-disk format inside a field.
+pub fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+/// Undo [escape].
+pub fn unescape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+
+    out
+}
+
 // write test
 #[cfg(test)]
 mod tests {
@@ -157,6 +501,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn slots() {
+        let star = many_stars();
+
+        assert_eq!(star.get(0), Some(&PaneId::Terminal(1)));
+        assert_eq!(star.get(2), Some(&PaneId::Terminal(3)));
+        assert_eq!(star.get(3), None);
+        assert_eq!(star.slot_of(&PaneId::Terminal(2)), Some(1));
+        assert_eq!(star.slot_of(&PaneId::Terminal(4)), None);
+    }
+
+    #[rstest]
+    #[case(PaneId::Terminal(1), [1, 2, 3], [2, 1, 3])]
+    #[case(PaneId::Terminal(2), [2, 1, 3], [1, 3, 2])]
+    #[case(PaneId::Terminal(3), [1, 3, 2], [1, 2, 3])]
+    #[case(PaneId::Terminal(4), [1, 2, 3], [1, 2, 3])]
+    fn move_up_and_down(
+        #[case] pane_id: PaneId,
+        #[case] expected_up: [u32; 3],
+        #[case] expected_down: [u32; 3],
+    ) {
+        let mut star = many_stars();
+        star.move_up(&pane_id);
+        assert_eq!(
+            star.pane_ids,
+            IndexSet::from(expected_up.map(PaneId::Terminal))
+        );
+
+        let mut star = many_stars();
+        star.move_down(&pane_id);
+        assert_eq!(
+            star.pane_ids,
+            IndexSet::from(expected_down.map(PaneId::Terminal))
+        );
+    }
+
     #[rstest]
     #[case(empty_star(), PaneId::Terminal(2), None)]
     #[case(one_star(), PaneId::Terminal(1), Some(&PaneId::Terminal(1)))]
@@ -184,4 +564,236 @@ mod tests {
         let got = star.previous(&current_id);
         assert_eq!(expected_id, got);
     }
+
+    fn key(tab_name: &str, title: &str) -> PaneKey {
+        PaneKey {
+            is_plugin: false,
+            tab_name: String::from(tab_name),
+            title: String::from(title),
+        }
+    }
+
+    fn entry(group: &str, tab_name: &str, title: &str) -> StarEntry {
+        StarEntry {
+            group: String::from(group),
+            key: key(tab_name, title),
+        }
+    }
+
+    fn groups() -> StarGroups {
+        StarGroups::new(vec![String::from("front"), String::from("back")])
+    }
+
+    #[test]
+    fn default_group() {
+        let star_groups = StarGroups::default();
+
+        assert_eq!(star_groups.group_count(), 1);
+        assert_eq!(star_groups.active_name(), DEFAULT_GROUP);
+    }
+
+    #[test]
+    fn switch_groups() {
+        let mut star_groups = groups();
+        assert_eq!(star_groups.active_name(), "front");
+
+        star_groups.next_group();
+        assert_eq!(star_groups.active_name(), "back");
+        assert_eq!(star_groups.first_name(), "front");
+
+        star_groups.next_group();
+        assert_eq!(star_groups.active_name(), "front");
+
+        star_groups.previous_group();
+        assert_eq!(star_groups.active_name(), "back");
+    }
+
+    #[test]
+    fn stars_in_groups() {
+        let mut star_groups = groups();
+
+        star_groups.active_mut().toggle(PaneId::Terminal(1));
+        star_groups.active_mut().toggle(PaneId::Terminal(2));
+        star_groups.next_group();
+        star_groups.active_mut().toggle(PaneId::Terminal(2));
+        star_groups.active_mut().toggle(PaneId::Terminal(3));
+
+        assert!(!star_groups.active().has(&PaneId::Terminal(1)));
+        assert_eq!(star_groups.groups_of(&PaneId::Terminal(1)), vec!["front"]);
+        assert_eq!(
+            star_groups.groups_of(&PaneId::Terminal(2)),
+            vec!["front", "back"]
+        );
+        assert!(star_groups.groups_of(&PaneId::Terminal(4)).is_empty());
+
+        star_groups.sync(&HashSet::from([PaneId::Terminal(1), PaneId::Terminal(3)]));
+
+        assert!(star_groups.groups_of(&PaneId::Terminal(2)).is_empty());
+        assert_eq!(star_groups.groups_of(&PaneId::Terminal(1)), vec!["front"]);
+        assert_eq!(star_groups.groups_of(&PaneId::Terminal(3)), vec!["back"]);
+    }
+
+    #[test]
+    fn restore() {
+        let mut star_groups = groups();
+        star_groups.set_pending(
+            vec![
+                entry("back", "Tab", "logs"),
+                entry("front", "Tab", "gone"),
+                entry("front", "Tab", "vim"),
+                entry("front", "Tab", "vim"),
+                entry("back", "Tab", "vim"),
+                entry("removed", "Tab", "vim"),
+            ],
+            0,
+        );
+
+        star_groups.restore(
+            &[
+                (PaneId::Terminal(1), key("Tab", "vim")),
+                (PaneId::Terminal(2), key("Tab", "logs")),
+                (PaneId::Terminal(3), key("Other", "vim")),
+            ],
+            0,
+        );
+
+        assert_eq!(
+            star_groups.active().pane_ids,
+            IndexSet::from([PaneId::Terminal(1)])
+        );
+        assert_eq!(
+            star_groups.groups_of(&PaneId::Terminal(1)),
+            vec!["front", "back"]
+        );
+        assert_eq!(star_groups.groups_of(&PaneId::Terminal(2)), vec!["back"]);
+        // The second "vim" entry could not claim the same pane twice in a group.
+        assert_eq!(
+            star_groups.pending,
+            vec![entry("front", "Tab", "gone"), entry("front", "Tab", "vim")]
+        );
+        // Entries of unknown groups are not matched, but saved again.
+        assert_eq!(star_groups.kept, vec![entry("removed", "Tab", "vim")]);
+
+        star_groups.restore(&[(PaneId::Terminal(4), key("Tab", "vim"))], 1000);
+
+        assert_eq!(
+            star_groups.active().pane_ids,
+            IndexSet::from([PaneId::Terminal(1), PaneId::Terminal(4)])
+        );
+        assert_eq!(star_groups.pending, vec![entry("front", "Tab", "gone")]);
+
+        // Entries that did not find their pane in time are no longer matched,
+        // but still saved.
+        star_groups.restore(&[], PENDING_MILLIS);
+        star_groups.restore(&[(PaneId::Terminal(5), key("Tab", "gone"))], PENDING_MILLIS);
+
+        assert!(star_groups.pending.is_empty());
+        assert!(!star_groups.active().pane_ids.contains(&PaneId::Terminal(5)));
+        assert_eq!(
+            star_groups.entries(|_| None),
+            vec![
+                entry("removed", "Tab", "vim"),
+                entry("front", "Tab", "gone"),
+            ]
+        );
+    }
+
+    #[test]
+    fn limit_kept_entries() {
+        let mut star_groups = groups();
+        let mut entries: Vec<_> = (0..KEPT_LIMIT + 2)
+            .map(|n| entry("front", "Tab", &n.to_string()))
+            .collect();
+        entries.insert(1, entry("removed", "Tab", "vim"));
+        star_groups.set_pending(entries, 0);
+
+        star_groups.restore(&[], PENDING_MILLIS);
+
+        // The oldest entries of configured groups are dropped first.
+        let kept = star_groups.entries(|_| None);
+        assert_eq!(kept.len(), KEPT_LIMIT + 1);
+        assert_eq!(kept[0], entry("removed", "Tab", "vim"));
+        assert_eq!(kept[1], entry("front", "Tab", "2"));
+    }
+
+    #[test]
+    fn entries() {
+        let mut star_groups = groups();
+        star_groups.groups[0] = many_stars();
+        star_groups.groups[1] = one_star();
+        star_groups.set_pending(vec![entry("front", "Tab", "pending")], 0);
+
+        let got = star_groups.entries(|id| match id {
+            PaneId::Terminal(2) => None,
+            PaneId::Terminal(id) => Some(key("Tab", &format!("Pane {}", id))),
+            PaneId::Plugin(_) => None,
+        });
+
+        assert_eq!(
+            got,
+            vec![
+                entry("front", "Tab", "Pane 1"),
+                entry("front", "Tab", "Pane 3"),
+                entry("back", "Tab", "Pane 1"),
+                entry("front", "Tab", "pending"),
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let entries = vec![
+            entry("default", "Tab 1", "vim"),
+            StarEntry {
+                group: String::from("ops"),
+                key: PaneKey {
+                    is_plugin: true,
+                    tab_name: String::from("Tab\t2"),
+                    title: String::from("back\\slash\nnew line"),
+                },
+            },
+            entry("", "", ""),
+        ];
+
+        let encoded = encode(&entries);
+        insta::assert_snapshot!(encoded);
+        assert_eq!(decode(&encoded, DEFAULT_GROUP), entries);
+    }
+
+    #[test]
+    fn decode_v1() {
+        let contents = "# zellij-pane-picker stars v1\nterminal\tTab 1\tvim\nplugin\tTab\\t2\tfilepicker\nops\tterminal\tTab\tvim\n";
+
+        assert_eq!(
+            decode(contents, "front"),
+            vec![
+                entry("front", "Tab 1", "vim"),
+                StarEntry {
+                    group: String::from("front"),
+                    key: PaneKey {
+                        is_plugin: true,
+                        tab_name: String::from("Tab\t2"),
+                        title: String::from("filepicker"),
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_skips_malformed_lines() {
+        let contents = "# comment\n\ndefault\tterminal\tTab\ndefault\tunknown\tTab\tvim\ndefault\tterminal\tTab\tvim\textra\ndefault\tplugin\tTab\tok\\";
+
+        assert_eq!(
+            decode(contents, DEFAULT_GROUP),
+            vec![StarEntry {
+                group: String::from("default"),
+                key: PaneKey {
+                    is_plugin: true,
+                    tab_name: String::from("Tab"),
+                    title: String::from("ok\\"),
+                },
+            }]
+        );
+    }
 }
@@ -0,0 +1,235 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const ELLIPSIS: &str = "...";
+
+/// Where clip puts the ellipsis when a string does not fit.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Ellipsis {
+    /// Keep the start of the string, e.g., `a very lo...`.
+    #[default]
+    End,
+    /// Keep the start and the end of the string, e.g., `~/src/...picker`.
+    /// Useful for paths, where the end tells them apart.
+    Middle,
+}
+
+/// A string clipped to a display width by clip.
+#[derive(Debug, PartialEq)]
+pub struct Clipped {
+    pub text: String,
+    head: usize,     // Chars of the original string kept before the ellipsis.
+    skipped: usize,  // Chars of the original string replaced by the ellipsis.
+    ellipsis: usize, // Chars of the ellipsis, 0 when nothing was clipped.
+}
+
+impl Clipped {
+    /// Return the char index in text of the char at index in the original string,
+    /// or None when the char was clipped.
+    pub fn index(&self, index: usize) -> Option<usize> {
+        if index < self.head {
+            Some(index)
+        } else if index < self.head + self.skipped {
+            None
+        } else {
+            Some(index - self.skipped + self.ellipsis)
+        }
+    }
+
+    /// Return the char indices in text of the chars at indices in the original string
+    /// that were not clipped, extended to the grapheme clusters they belong to,
+    /// so that a matched letter is highlighted together with its combining marks.
+    pub fn indices<I>(&self, indices: I) -> Vec<usize>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let kept: Vec<usize> = indices.into_iter().filter_map(|i| self.index(i)).collect();
+
+        let mut out = Vec::new();
+        let mut start = 0;
+        for grapheme in self.text.graphemes(true) {
+            let cluster = start..start + grapheme.chars().count();
+            if kept.iter().any(|i| cluster.contains(i)) {
+                out.extend(cluster.clone());
+            }
+            start = cluster.end;
+        }
+        out
+    }
+}
+
+/// Return the number of terminal cells that s takes.
+pub fn width(s: &str) -> usize {
+    s.width()
+}
+
+/// Clip s to at most max_width cells, replacing what does not fit with `...`.
+///
+/// Whole grapheme clusters are kept or dropped, so accents stay on their letters
+/// and wide chars are never cut in half. The result can be narrower than
+/// max_width when a wide char does not fit next to the ellipsis.
+pub fn clip(s: &str, max_width: usize, ellipsis: Ellipsis) -> Clipped {
+    let chars = s.chars().count();
+    if width(s) <= max_width {
+        return Clipped {
+            text: s.to_string(),
+            head: chars,
+            skipped: 0,
+            ellipsis: 0,
+        };
+    }
+
+    if max_width < ELLIPSIS.len() {
+        return Clipped {
+            text: ELLIPSIS[..max_width].to_string(),
+            head: 0,
+            skipped: chars,
+            ellipsis: max_width,
+        };
+    }
+
+    let budget = max_width - ELLIPSIS.len();
+    let graphemes: Vec<&str> = s.graphemes(true).collect();
+    let head_budget = match ellipsis {
+        Ellipsis::End => budget,
+        Ellipsis::Middle => budget - budget / 2,
+    };
+
+    let mut head_width = 0;
+    let head = graphemes
+        .iter()
+        .take_while(|g| {
+            head_width += width(g);
+            head_width <= head_budget
+        })
+        .count();
+    let head_text: String = graphemes[..head].concat();
+
+    // The tail gets the width that the head leaves, e.g., after a wide char did not fit.
+    let tail_budget = match ellipsis {
+        Ellipsis::End => 0,
+        Ellipsis::Middle => budget - width(&head_text),
+    };
+    let mut tail_width = 0;
+    let tail = graphemes[head..]
+        .iter()
+        .rev()
+        .take_while(|g| {
+            tail_width += width(g);
+            tail_width <= tail_budget
+        })
+        .count();
+    let tail_text: String = graphemes[graphemes.len() - tail..].concat();
+
+    let head_chars = head_text.chars().count();
+    Clipped {
+        text: format!("{}{}{}", head_text, ELLIPSIS, tail_text),
+        head: head_chars,
+        skipped: chars - head_chars - tail_text.chars().count(),
+        ellipsis: ELLIPSIS.len(),
+    }
+}
+
+/// Pad s with spaces on the right to width cells.
+pub fn pad(s: &str, width: usize) -> String {
+    let padding = width.saturating_sub(self::width(s));
+    format!("{}{}", s, " ".repeat(padding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::fits("Pane 1", 10, Ellipsis::End, "Pane 1")]
+    #[case::exact("Pane 1", 6, Ellipsis::End, "Pane 1")]
+    #[case::end("a very long title", 10, Ellipsis::End, "a very ...")]
+    #[case::middle("~/src/zellij-pane-picker", 15, Ellipsis::Middle, "~/src/...picker")]
+    #[case::middle_odd("abcdefghij", 8, Ellipsis::Middle, "abc...ij")]
+    #[case::only_ellipsis("abcdef", 3, Ellipsis::Middle, "...")]
+    #[case::narrower_than_ellipsis("abcdef", 2, Ellipsis::End, "..")]
+    #[case::zero_width("abcdef", 0, Ellipsis::End, "")]
+    #[case::empty("", 0, Ellipsis::End, "")]
+    #[case::accented_fits("déjà vu", 7, Ellipsis::End, "déjà vu")]
+    #[case::accented("café crème brûlée", 10, Ellipsis::End, "café cr...")]
+    #[case::combining_mark("cafe\u{301} au lait", 7, Ellipsis::End, "cafe\u{301}...")]
+    #[case::cjk_fits("日本語", 6, Ellipsis::End, "日本語")]
+    #[case::cjk("日本語のタイトル", 9, Ellipsis::End, "日本語...")]
+    #[case::cjk_wide_char_does_not_fit("日本語のタイトル", 8, Ellipsis::End, "日本...")]
+    #[case::cjk_middle("日本語のタイトル", 10, Ellipsis::Middle, "日本...ル")]
+    #[case::mixed_width("ab日本cd", 6, Ellipsis::End, "ab...")]
+    #[case::emoji("🦀 rust crab", 8, Ellipsis::End, "🦀 ru...")]
+    #[case::emoji_zwj("👩‍💻 coding", 6, Ellipsis::End, "👩‍💻 ...")]
+    #[case::flag_does_not_fit_head("🇯🇵 tokyo", 5, Ellipsis::Middle, "...yo")]
+    #[case::middle_keeps_tail("src/main.rs", 8, Ellipsis::Middle, "src...rs")]
+    fn clip_text(
+        #[case] s: &str,
+        #[case] max_width: usize,
+        #[case] ellipsis: Ellipsis,
+        #[case] expected: &str,
+    ) {
+        let clipped = clip(s, max_width, ellipsis);
+
+        assert_eq!(clipped.text, expected);
+        assert!(width(&clipped.text) <= max_width);
+    }
+
+    #[rstest]
+    #[case::ascii("vim", 3)]
+    #[case::accented("déjà", 4)]
+    #[case::combining_mark("e\u{301}", 1)]
+    #[case::cjk("日本語", 6)]
+    #[case::emoji("🦀", 2)]
+    #[case::emoji_zwj("👩‍💻", 2)]
+    fn display_width(#[case] s: &str, #[case] expected: usize) {
+        assert_eq!(width(s), expected);
+    }
+
+    #[rstest]
+    #[case::not_clipped("abcdef", 10, Ellipsis::End, 5, Some(5))]
+    #[case::end_kept("abcdef", 5, Ellipsis::End, 1, Some(1))]
+    #[case::end_clipped("abcdef", 5, Ellipsis::End, 2, None)]
+    #[case::middle_head("abcdefgh", 7, Ellipsis::Middle, 1, Some(1))]
+    #[case::middle_skipped("abcdefgh", 7, Ellipsis::Middle, 4, None)]
+    #[case::middle_tail("abcdefgh", 7, Ellipsis::Middle, 7, Some(6))]
+    #[case::multibyte_kept("日本語のタイトル", 9, Ellipsis::End, 2, Some(2))]
+    #[case::multibyte_clipped("日本語のタイトル", 9, Ellipsis::End, 3, None)]
+    #[case::combining_mark("cafe\u{301} au lait", 7, Ellipsis::End, 4, Some(4))]
+    fn map_index(
+        #[case] s: &str,
+        #[case] max_width: usize,
+        #[case] ellipsis: Ellipsis,
+        #[case] index: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_eq!(clip(s, max_width, ellipsis).index(index), expected);
+    }
+
+    #[rstest]
+    #[case::not_clipped("abcdef", 10, Ellipsis::End, &[1, 3], &[1, 3])]
+    #[case::clipped("abcdef", 5, Ellipsis::End, &[0, 3], &[0])]
+    #[case::middle_tail("abcdefgh", 7, Ellipsis::Middle, &[0, 7], &[0, 6])]
+    #[case::combining_mark("cafe\u{301} au lait", 7, Ellipsis::End, &[3], &[3, 4])]
+    #[case::combining_mark_clipped("cafe\u{301} au lait", 4, Ellipsis::End, &[3], &[])]
+    #[case::zwj_emoji("👩‍💻 coding", 6, Ellipsis::End, &[0], &[0, 1, 2])]
+    fn map_indices(
+        #[case] s: &str,
+        #[case] max_width: usize,
+        #[case] ellipsis: Ellipsis,
+        #[case] indices: &[usize],
+        #[case] expected: &[usize],
+    ) {
+        let got = clip(s, max_width, ellipsis).indices(indices.iter().copied());
+
+        assert_eq!(got, expected);
+    }
+
+    #[rstest]
+    #[case::ascii("Tab", 5, "Tab  ")]
+    #[case::cjk("日本", 5, "日本 ")]
+    #[case::too_long("日本語", 4, "日本語")]
+    fn pad_text(#[case] s: &str, #[case] width: usize, #[case] expected: &str) {
+        assert_eq!(pad(s, width), expected);
+    }
+}
@@ -0,0 +1,94 @@
+use std::ops::RangeBounds;
+use zellij_tile::prelude::Text;
+
+/// The number of emphasis colors that Zellij gives plugins from the palette.
+const COLORS: usize = 4;
+
+/// The palette colors of the parts of the picker,
+/// given as emphasis indices. None leaves the part in the default color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// The `[SEARCH]` label and the labels of the other prompts.
+    pub prompt: Option<usize>,
+    /// The text typed into the prompt.
+    pub query: Option<usize>,
+    /// The star and slot columns and the star groups.
+    pub star: Option<usize>,
+    /// The title of the focused pane.
+    pub current_pane: Option<usize>,
+    /// The title of the previously focused pane.
+    pub previous_pane: Option<usize>,
+    /// The characters that match the search query.
+    pub matched: Option<usize>,
+    /// The header row of the tables, the tab headers of the tree layout,
+    /// and the title of the preview.
+    pub header: Option<usize>,
+    /// The titles of exited panes and resurrectable sessions.
+    pub dimmed: Option<usize>,
+    /// The marks of marked panes and the marker of the active tab.
+    pub mark: Option<usize>,
+    /// The names of running sessions.
+    pub session: Option<usize>,
+    /// The error shown above the list.
+    pub error: Option<usize>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            prompt: Some(1),
+            query: Some(3),
+            star: Some(0),
+            current_pane: None,
+            previous_pane: None,
+            matched: Some(3),
+            header: None,
+            dimmed: None,
+            mark: Some(1),
+            session: Some(2),
+            error: Some(1),
+        }
+    }
+}
+
+/// Parse a color of the theme: an emphasis index from 0 to 3, or `none`.
+/// Return None for invalid values.
+pub fn parse_color(value: &str) -> Option<Option<usize>> {
+    match value.trim() {
+        "none" => Some(None),
+        value => value.parse().ok().filter(|index| *index < COLORS).map(Some),
+    }
+}
+
+/// Color the chars of text in range, or leave them as they are without a color.
+pub fn color_range<R: RangeBounds<usize>>(text: Text, color: Option<usize>, range: R) -> Text {
+    match color {
+        Some(color) => text.color_range(color, range),
+        None => text,
+    }
+}
+
+/// Color the chars of text at indices, or leave them as they are without a color.
+pub fn color_indices(text: Text, color: Option<usize>, indices: Vec<usize>) -> Text {
+    match color {
+        Some(color) if !indices.is_empty() => text.color_indices(color, indices),
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::first("0", Some(Some(0)))]
+    #[case::last(" 3 ", Some(Some(3)))]
+    #[case::none("none", Some(None))]
+    #[case::out_of_palette("4", None)]
+    #[case::negative("-1", None)]
+    #[case::name("red", None)]
+    fn parse(#[case] value: &str, #[case] expected: Option<Option<usize>>) {
+        assert_eq!(parse_color(value), expected);
+    }
+}